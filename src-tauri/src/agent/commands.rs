@@ -1,22 +1,37 @@
 use std::fs;
 
+use tauri::AppHandle;
+
+use crate::agent::instructions::discover_instructions;
+use crate::agent::mode::{ensure_can_read, ensure_can_run, ensure_can_write};
 use crate::agent::paths::{
-    canonicalize_workspace, resolve_path_in_workspace, resolve_workdir_in_workspace,
+    ensure_not_protected, resolve_agent_workspace, resolve_path_in_workspace,
+    resolve_workdir_in_workspace,
 };
 use crate::agent::runner::run_shell_command;
+use crate::core::constants::DEFAULT_COMMAND_TIMEOUT_MS;
+use crate::core::workspace::canonicalize_workspace;
 use crate::agent::types::{
     AgentDeleteFileResult, AgentReadFileResult, AgentRunCommandResult, AgentWriteFileResult,
+    ProjectInstructions,
 };
+use crate::git::worktree::thread_workspace_path;
+use crate::storage::db::{open_db, require_project, require_thread};
+use crate::storage::io::ensure_storage_ready;
+use crate::storage::paths::storage_root;
+use crate::storage::project_settings::{merge_settings, read_workspace_settings};
 
 #[tauri::command]
 pub fn agent_read_file(
-    workspace_path: String,
+    app: AppHandle,
+    thread_id: String,
     path: String,
     offset: Option<usize>,
     limit: Option<usize>,
 ) -> Result<AgentReadFileResult, String> {
-    let workspace_root = canonicalize_workspace(&workspace_path)?;
-    let resolved = resolve_path_in_workspace(&workspace_root, &path, false)?;
+    let workspace = resolve_agent_workspace(&app, &thread_id)?;
+    ensure_can_read(workspace.mode)?;
+    let resolved = resolve_path_in_workspace(&workspace.root, &path, false)?;
     if !resolved.is_file() {
         return Err("Path is not a file".to_string());
     }
@@ -46,13 +61,16 @@ pub fn agent_read_file(
 
 #[tauri::command]
 pub fn agent_write_file(
-    workspace_path: String,
+    app: AppHandle,
+    thread_id: String,
     path: String,
     content: String,
     create_dirs: Option<bool>,
 ) -> Result<AgentWriteFileResult, String> {
-    let workspace_root = canonicalize_workspace(&workspace_path)?;
-    let resolved = resolve_path_in_workspace(&workspace_root, &path, true)?;
+    let workspace = resolve_agent_workspace(&app, &thread_id)?;
+    ensure_can_write(workspace.mode)?;
+    let resolved = resolve_path_in_workspace(&workspace.root, &path, true)?;
+    ensure_not_protected(&workspace, &resolved)?;
 
     if let Some(parent) = resolved.parent() {
         if create_dirs.unwrap_or(false) {
@@ -71,12 +89,15 @@ pub fn agent_write_file(
 
 #[tauri::command]
 pub fn agent_delete_file(
-    workspace_path: String,
+    app: AppHandle,
+    thread_id: String,
     path: String,
     allow_missing: Option<bool>,
 ) -> Result<AgentDeleteFileResult, String> {
-    let workspace_root = canonicalize_workspace(&workspace_path)?;
-    let resolved = resolve_path_in_workspace(&workspace_root, &path, true)?;
+    let workspace = resolve_agent_workspace(&app, &thread_id)?;
+    ensure_can_write(workspace.mode)?;
+    let resolved = resolve_path_in_workspace(&workspace.root, &path, true)?;
+    ensure_not_protected(&workspace, &resolved)?;
 
     if !resolved.exists() {
         if allow_missing.unwrap_or(true) {
@@ -101,14 +122,55 @@ pub fn agent_delete_file(
 
 #[tauri::command]
 pub fn agent_run_command(
-    workspace_path: String,
+    app: AppHandle,
+    thread_id: String,
     command: String,
     workdir: Option<String>,
     timeout_ms: Option<u64>,
 ) -> Result<AgentRunCommandResult, String> {
-    let workspace_root = canonicalize_workspace(&workspace_path)?;
-    let resolved_workdir = resolve_workdir_in_workspace(&workspace_root, workdir)?;
-    let timeout = timeout_ms.unwrap_or(120_000).clamp(100, 300_000);
+    let workspace = resolve_agent_workspace(&app, &thread_id)?;
+    ensure_can_run(workspace.mode, &command)?;
+    let resolved_workdir = resolve_workdir_in_workspace(&workspace.root, workdir)?;
+    let timeout = timeout_ms
+        .or(workspace.settings.command_timeout_ms)
+        .unwrap_or(DEFAULT_COMMAND_TIMEOUT_MS)
+        .clamp(100, 300_000);
+
+    run_shell_command(
+        &command,
+        &resolved_workdir,
+        timeout,
+        &workspace.settings.env,
+    )
+}
+
+#[tauri::command]
+pub fn load_project_instructions(
+    app: AppHandle,
+    project_id: String,
+    thread_id: Option<String>,
+) -> Result<ProjectInstructions, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let conn = open_db(&root)?;
+    let project = require_project(&conn, &project_id)?;
+    let workspace_path = match thread_id {
+        Some(thread_id) => thread_workspace_path(&project, &require_thread(&conn, &thread_id)?),
+        None => project.workspace_path.clone(),
+    };
+    let workspace = canonicalize_workspace(&workspace_path)?;
 
-    run_shell_command(&command, &resolved_workdir, timeout)
+    let (files, mut warnings) = discover_instructions(&workspace);
+    let workspace_settings = read_workspace_settings(&workspace).unwrap_or_else(|e| {
+        warnings.push(e);
+        None
+    });
+    let settings = merge_settings(workspace_settings.as_ref(), &project.settings);
+
+    Ok(ProjectInstructions {
+        workspace_path: workspace.to_string_lossy().to_string(),
+        settings_instructions: settings.instructions,
+        files,
+        warnings,
+    })
 }
@@ -0,0 +1,222 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::agent::types::{InstructionFile, InstructionKind};
+use crate::core::constants::{
+    INSTRUCTION_FILE_NAME, INSTRUCTION_SKIPPED_DIRS, MAX_INSTRUCTION_DEPTH,
+    MAX_INSTRUCTION_FILE_BYTES, MAX_INSTRUCTION_INCLUDE_DEPTH, PROJECT_CONFIG_DIR, RULES_DIR,
+};
+
+struct Discovery<'a> {
+    root: &'a Path,
+    files: Vec<InstructionFile>,
+    warnings: Vec<String>,
+}
+
+fn relative(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+pub fn discover_instructions(root: &Path) -> (Vec<InstructionFile>, Vec<String>) {
+    let mut discovery = Discovery {
+        root,
+        files: Vec::new(),
+        warnings: Vec::new(),
+    };
+    discovery.visit(root, 0);
+    (discovery.files, discovery.warnings)
+}
+
+impl Discovery<'_> {
+    fn visit(&mut self, dir: &Path, depth: usize) {
+        let agents = dir.join(INSTRUCTION_FILE_NAME);
+        if agents.is_file() {
+            self.load(&agents, dir, InstructionKind::Agents);
+        }
+
+        let rules_dir = dir.join(PROJECT_CONFIG_DIR).join(RULES_DIR);
+        let mut rules = fs::read_dir(&rules_dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && path.extension().is_some_and(|e| e == "md"))
+            .collect::<Vec<_>>();
+        rules.sort();
+        for rule in rules {
+            self.load(&rule, dir, InstructionKind::Rule);
+        }
+
+        if depth >= MAX_INSTRUCTION_DEPTH {
+            return;
+        }
+        let mut subdirs = fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+            .filter(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                !name.starts_with('.') && !INSTRUCTION_SKIPPED_DIRS.contains(&name.as_str())
+            })
+            .map(|entry| entry.path())
+            .collect::<Vec<_>>();
+        subdirs.sort();
+        for subdir in subdirs {
+            self.visit(&subdir, depth + 1);
+        }
+    }
+
+    fn load(&mut self, path: &Path, scope: &Path, kind: InstructionKind) {
+        // Discovered files may be symlinks; hold them to the same rule as
+        // includes so nothing outside the workspace reaches the prompt.
+        let inside = fs::canonicalize(path).is_ok_and(|resolved| resolved.starts_with(self.root));
+        if !inside {
+            self.warnings.push(format!(
+                "{} points outside the workspace and was skipped",
+                relative(self.root, path)
+            ));
+            return;
+        }
+
+        let mut includes = Vec::new();
+        let mut stack = Vec::new();
+        if let Some(content) = self.read(path, &mut stack, &mut includes) {
+            self.files.push(InstructionFile {
+                path: relative(self.root, path),
+                scope: relative(self.root, scope),
+                kind,
+                content,
+                includes,
+            });
+        }
+    }
+
+    fn read(
+        &mut self,
+        path: &Path,
+        stack: &mut Vec<PathBuf>,
+        includes: &mut Vec<String>,
+    ) -> Option<String> {
+        let shown = relative(self.root, path);
+        let size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        if size > MAX_INSTRUCTION_FILE_BYTES {
+            self.warnings.push(format!(
+                "{shown} is larger than {MAX_INSTRUCTION_FILE_BYTES} bytes and was skipped"
+            ));
+            return None;
+        }
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => {
+                self.warnings.push(format!("{shown}: {e}"));
+                return None;
+            }
+        };
+
+        stack.push(path.to_path_buf());
+        let mut out = Vec::new();
+        for line in text.lines() {
+            match self.include_target(path, line, stack) {
+                Some(target) => match self.read(&target, stack, includes) {
+                    Some(included) => {
+                        includes.push(relative(self.root, &target));
+                        out.push(included);
+                    }
+                    None => out.push(line.to_string()),
+                },
+                None => out.push(line.to_string()),
+            }
+        }
+        stack.pop();
+        Some(out.join("\n"))
+    }
+
+    fn include_target(&mut self, from: &Path, line: &str, stack: &[PathBuf]) -> Option<PathBuf> {
+        let target = line.trim().strip_prefix('@')?;
+        if target.is_empty() || target.contains(char::is_whitespace) || !target.ends_with(".md") {
+            return None;
+        }
+
+        let shown = relative(self.root, from);
+        let Ok(resolved) = fs::canonicalize(from.parent()?.join(target)) else {
+            self.warnings
+                .push(format!("{shown}: included file {target} not found"));
+            return None;
+        };
+        if !resolved.starts_with(self.root) {
+            self.warnings.push(format!(
+                "{shown}: include {target} is outside the workspace"
+            ));
+            return None;
+        }
+        if !resolved.is_file() {
+            self.warnings
+                .push(format!("{shown}: include {target} is not a file"));
+            return None;
+        }
+        if stack
+            .iter()
+            .any(|p| fs::canonicalize(p).is_ok_and(|p| p == resolved))
+        {
+            self.warnings
+                .push(format!("{shown}: include {target} forms a cycle"));
+            return None;
+        }
+        if stack.len() > MAX_INSTRUCTION_INCLUDE_DEPTH {
+            self.warnings.push(format!(
+                "{shown}: include {target} is nested more than {MAX_INSTRUCTION_INCLUDE_DEPTH} levels deep"
+            ));
+            return None;
+        }
+        Some(resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("kodit-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join(PROJECT_CONFIG_DIR).join(RULES_DIR)).unwrap();
+        fs::canonicalize(dir).unwrap()
+    }
+
+    #[test]
+    fn resolves_includes_and_cycles() {
+        let root = workspace("instructions-includes");
+        fs::write(root.join(INSTRUCTION_FILE_NAME), "top\n@docs/a.md").unwrap();
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join("docs/a.md"), "a\n@a.md").unwrap();
+
+        let (files, warnings) = discover_instructions(&root);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].content, "top\na\n@a.md");
+        assert_eq!(files[0].includes, ["docs/a.md"]);
+        assert!(warnings.iter().any(|w| w.contains("cycle")));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn skips_symlinks_out_of_the_workspace() {
+        let root = workspace("instructions-symlink");
+        let outside = root.with_extension("secret");
+        fs::write(&outside, "secret").unwrap();
+        let rules = root.join(PROJECT_CONFIG_DIR).join(RULES_DIR);
+        std::os::unix::fs::symlink(&outside, rules.join("x.md")).unwrap();
+        fs::write(rules.join("y.md"), "fine").unwrap();
+
+        let (files, warnings) = discover_instructions(&root);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].content, "fine");
+        assert!(warnings.iter().any(|w| w.contains("outside the workspace")));
+        fs::remove_dir_all(root).unwrap();
+        fs::remove_file(outside).unwrap();
+    }
+}
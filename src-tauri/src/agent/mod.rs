@@ -1,4 +1,6 @@
 pub mod commands;
+pub mod instructions;
+pub mod mode;
 pub mod paths;
 pub mod runner;
 pub mod types;
@@ -0,0 +1,222 @@
+use crate::core::models::AgentMode;
+
+const READ_ONLY_PROGRAMS: &[&str] = &[
+    "basename", "cat", "cd", "cut", "diff", "dirname", "du", "echo", "fd", "file", "find", "grep",
+    "head", "ls", "nl", "pwd", "realpath", "rg", "sort", "stat", "tail", "tree", "uniq", "wc",
+    "which",
+];
+
+const READ_ONLY_GIT_SUBCOMMANDS: &[&str] = &[
+    "blame",
+    "diff",
+    "grep",
+    "log",
+    "ls-files",
+    "rev-parse",
+    "show",
+    "status",
+];
+
+const WRITING_FLAGS: &[(&str, &[&str])] = &[
+    (
+        "find",
+        &[
+            "-delete", "-exec", "-execdir", "-ok", "-okdir", "-fprint", "-fprint0", "-fprintf",
+            "-fls",
+        ],
+    ),
+    ("sort", &["-o", "--output", "--compress-program"]),
+    ("git", &["--output", "-O", "--open-files-in-pager"]),
+    ("fd", &["-x", "--exec", "-X", "--exec-batch"]),
+    ("rg", &["--pre", "--hostname-bin"]),
+    ("tree", &["-o"]),
+    ("file", &["-C", "--compile"]),
+];
+
+const SHELL_SPECIAL: &[char] = &[
+    '>', '<', '`', '$', '(', ')', '\n', '\r', '\'', '"', '\\', '*', '?', '[', ']', '{', '}', '~',
+];
+
+fn mode_name(mode: AgentMode) -> &'static str {
+    match mode {
+        AgentMode::Build => "Build",
+        AgentMode::Plan => "Plan",
+        AgentMode::Ask => "Ask",
+        AgentMode::Review => "Review",
+    }
+}
+
+pub fn ensure_can_read(mode: AgentMode) -> Result<(), String> {
+    match mode {
+        AgentMode::Ask => Err("Ask mode has no tools; files cannot be read".to_string()),
+        _ => Ok(()),
+    }
+}
+
+pub fn ensure_can_write(mode: AgentMode) -> Result<(), String> {
+    match mode {
+        AgentMode::Build => Ok(()),
+        AgentMode::Ask => Err("Ask mode has no tools; files cannot be changed".to_string()),
+        _ => Err(format!(
+            "{} mode is read-only; files cannot be changed",
+            mode_name(mode)
+        )),
+    }
+}
+
+pub fn ensure_can_run(mode: AgentMode, command: &str) -> Result<(), String> {
+    match mode {
+        AgentMode::Build => Ok(()),
+        AgentMode::Ask => Err("Ask mode has no tools; commands cannot be run".to_string()),
+        _ if is_read_only_command(command) => Ok(()),
+        _ => Err(format!(
+            "{} mode is read-only; `{}` may change the workspace and was not run",
+            mode_name(mode),
+            command.trim()
+        )),
+    }
+}
+
+pub fn is_read_only_command(command: &str) -> bool {
+    if command.contains(SHELL_SPECIAL) {
+        return false;
+    }
+    command
+        .split("&&")
+        .flat_map(|part| part.split("||"))
+        .flat_map(|part| part.split([';', '|']))
+        .all(|step| !step.contains('&') && is_read_only_step(step))
+}
+
+fn is_read_only_step(step: &str) -> bool {
+    let mut words = step.split_whitespace();
+    let Some(program) = words.next() else {
+        return false;
+    };
+    let args = words.collect::<Vec<_>>();
+
+    let known = match program {
+        // Options before the subcommand (`-c`, `-C`, `--exec-path`, ...)
+        // change what git runs, so the subcommand must come first.
+        "git" => args
+            .iter()
+            .find(|arg| **arg != "--no-pager")
+            .is_some_and(|sub| READ_ONLY_GIT_SUBCOMMANDS.contains(sub)),
+        // `uniq INPUT OUTPUT` writes its second operand.
+        "uniq" => args.iter().filter(|arg| !arg.starts_with('-')).count() <= 1,
+        _ => READ_ONLY_PROGRAMS.contains(&program),
+    };
+    if !known {
+        return false;
+    }
+
+    let forbidden = WRITING_FLAGS
+        .iter()
+        .find(|(name, _)| *name == program)
+        .map(|(_, flags)| *flags)
+        .unwrap_or_default();
+    !args
+        .iter()
+        .any(|arg| forbidden.iter().any(|flag| uses_flag(arg, flag)))
+}
+
+fn uses_flag(arg: &str, flag: &str) -> bool {
+    if flag.starts_with("--") {
+        return arg == flag || arg.starts_with(&format!("{flag}="));
+    }
+    if arg.starts_with(flag) {
+        return true;
+    }
+    match (flag.len(), flag.chars().nth(1)) {
+        (2, Some(letter)) => {
+            arg.starts_with('-') && !arg.starts_with("--") && arg[1..].contains(letter)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allows_plain_read_only_commands() {
+        for command in [
+            "ls -la",
+            "git status",
+            "git --no-pager log --oneline -n 5",
+            "rg -n fn src | head -20",
+            "cat Cargo.toml && wc -l src/main.rs",
+            "find . -name main.rs",
+            "sort -r names.txt | uniq -c",
+        ] {
+            assert!(is_read_only_command(command), "{command}");
+        }
+    }
+
+    #[test]
+    fn refuses_unknown_programs_and_subcommands() {
+        for command in ["rm -rf target", "git checkout main", "ls; touch x", "cargo build"] {
+            assert!(!is_read_only_command(command), "{command}");
+        }
+    }
+
+    #[test]
+    fn refuses_shell_rewriting() {
+        for command in [
+            "find . -name '*.rs' '-delete'",
+            "find . -del\\ete",
+            "find . -\"delete\"",
+            "ls *.rs",
+            "cat ~/.ssh/id_rsa",
+            "echo $HOME",
+            "echo hi > out.txt",
+            "ls & touch x",
+        ] {
+            assert!(!is_read_only_command(command), "{command}");
+        }
+    }
+
+    #[test]
+    fn refuses_writing_flags() {
+        for command in [
+            "find . -delete",
+            "find . -fprint0 out",
+            "find . -exec rm {} +",
+            "sort -o out in",
+            "sort -oout in",
+            "sort -ro out in",
+            "sort --output=out in",
+            "git grep -Orm pat",
+            "git grep -rO pat",
+            "git diff --output=/tmp/x",
+            "tree -o out",
+            "fd -X rm",
+            "rg --pre sh pat",
+            "uniq in out",
+        ] {
+            assert!(!is_read_only_command(command), "{command}");
+        }
+    }
+
+    #[test]
+    fn refuses_git_global_options() {
+        for command in [
+            "git -c core.pager=sh log",
+            "git -C /tmp status",
+            "git --exec-path=/tmp log",
+        ] {
+            assert!(!is_read_only_command(command), "{command}");
+        }
+    }
+
+    #[test]
+    fn modes_gate_tools() {
+        assert!(ensure_can_write(AgentMode::Build).is_ok());
+        assert!(ensure_can_write(AgentMode::Plan).is_err());
+        assert!(ensure_can_read(AgentMode::Ask).is_err());
+        assert!(ensure_can_run(AgentMode::Review, "git diff").is_ok());
+        assert!(ensure_can_run(AgentMode::Review, "git commit -m x").is_err());
+        assert!(ensure_can_run(AgentMode::Ask, "ls").is_err());
+    }
+}
@@ -1,15 +1,48 @@
 use std::fs;
 use std::path::{Component, Path, PathBuf};
 
-pub fn canonicalize_workspace(workspace_path: &str) -> Result<PathBuf, String> {
-    let workspace = PathBuf::from(workspace_path);
-    if !workspace.is_absolute() {
-        return Err("Workspace path must be absolute".to_string());
-    }
-    if !workspace.exists() {
-        return Err("Workspace path does not exist".to_string());
+use tauri::AppHandle;
+
+use crate::core::models::{AgentMode, ProjectSettings};
+use crate::core::workspace::canonicalize_workspace;
+use crate::git::worktree::thread_workspace_path;
+use crate::storage::db::{open_db, require_project, require_thread};
+use crate::storage::io::ensure_storage_ready;
+use crate::storage::paths::storage_root;
+use crate::storage::project_settings::{merge_settings, protected_match, read_workspace_settings};
+
+pub struct AgentWorkspace {
+    pub root: PathBuf,
+    pub mode: AgentMode,
+    pub settings: ProjectSettings,
+}
+
+pub fn resolve_agent_workspace(app: &AppHandle, thread_id: &str) -> Result<AgentWorkspace, String> {
+    let storage = storage_root(app)?;
+    let _lock = ensure_storage_ready(&storage)?;
+    let conn = open_db(&storage)?;
+    let thread = require_thread(&conn, thread_id)?;
+    let project = require_project(&conn, &thread.project_id)?;
+    let root = canonicalize_workspace(&thread_workspace_path(&project, &thread))?;
+
+    // A broken config file must not quietly drop its protected paths.
+    let workspace_settings = read_workspace_settings(&root)?;
+    Ok(AgentWorkspace {
+        settings: merge_settings(workspace_settings.as_ref(), &project.settings),
+        root,
+        mode: thread.mode,
+    })
+}
+
+pub fn ensure_not_protected(workspace: &AgentWorkspace, resolved: &Path) -> Result<(), String> {
+    let relative = resolved.strip_prefix(&workspace.root).unwrap_or(resolved);
+    match protected_match(&workspace.settings, relative) {
+        Some(protected) => Err(format!(
+            "{} is protected by the project settings ({protected})",
+            relative.display()
+        )),
+        None => Ok(()),
     }
-    fs::canonicalize(workspace).map_err(|e| e.to_string())
 }
 
 pub fn resolve_path_in_workspace(
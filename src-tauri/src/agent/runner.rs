@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::path::Path;
 use std::process::{Command, Stdio};
 use std::thread;
@@ -9,6 +10,7 @@ pub fn run_shell_command(
     command: &str,
     workdir: &Path,
     timeout_ms: u64,
+    env: &BTreeMap<String, String>,
 ) -> Result<AgentRunCommandResult, String> {
     let mut process = if cfg!(target_os = "windows") {
         let mut cmd = Command::new("cmd");
@@ -22,6 +24,7 @@ pub fn run_shell_command(
 
     let mut child = process
         .current_dir(workdir)
+        .envs(env)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn()
@@ -31,3 +31,27 @@ pub struct AgentRunCommandResult {
     pub stderr: String,
     pub timed_out: bool,
 }
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstructionKind {
+    Agents,
+    Rule,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InstructionFile {
+    pub path: String,
+    pub scope: String,
+    pub kind: InstructionKind,
+    pub content: String,
+    pub includes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectInstructions {
+    pub workspace_path: String,
+    pub settings_instructions: Option<String>,
+    pub files: Vec<InstructionFile>,
+    pub warnings: Vec<String>,
+}
@@ -1,4 +1,7 @@
-use crate::{agent, auth, storage, ui};
+use std::time::Duration;
+
+use crate::core::constants::{AUTH_LOCK_CHECK_INTERVAL_SECS, BACKUP_CHECK_INTERVAL_SECS};
+use crate::{agent, auth, diff, git, storage, ui};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -6,28 +9,96 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_os::init())
         .setup(|app| {
-            let root = storage::paths::storage_root(&app.handle()).map_err(std::io::Error::other)?;
+            let root = storage::paths::storage_root(app.handle()).map_err(std::io::Error::other)?;
+            // Nothing else is writing yet, so every leftover temp file is stale.
+            let _ = storage::health::cleanup_tmp_files(&root, Duration::ZERO);
             storage::io::ensure_storage_ready(&root).map_err(std::io::Error::other)?;
+            // Trash cleanup is best-effort and must not keep the app from starting.
+            let _ = storage::trash::purge_expired_trash(&root);
+            std::thread::spawn(move || loop {
+                let _ = storage::backup::run_scheduled_backup(&root);
+                std::thread::sleep(Duration::from_secs(BACKUP_CHECK_INTERVAL_SECS));
+            });
+            std::thread::spawn(|| loop {
+                auth::passphrase::expire_session();
+                std::thread::sleep(Duration::from_secs(AUTH_LOCK_CHECK_INTERVAL_SECS));
+            });
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             storage::meta_commands::storage_info,
+            storage::meta_commands::storage_health,
+            storage::meta_commands::repair_storage,
             storage::project_thread_commands::upsert_project,
             storage::project_thread_commands::list_projects,
+            storage::project_thread_commands::relocate_project,
             storage::project_thread_commands::upsert_thread,
             storage::project_thread_commands::list_threads,
+            storage::project_thread_commands::set_project_archived,
+            storage::project_thread_commands::get_project_settings,
+            storage::project_thread_commands::set_project_settings,
+            storage::project_thread_commands::set_thread_archived,
+            storage::trash_commands::delete_project,
+            storage::trash_commands::delete_thread,
+            storage::trash_commands::list_trash,
+            storage::trash_commands::restore_project,
+            storage::trash_commands::restore_thread,
+            storage::trash_commands::empty_trash,
+            storage::search_commands::search_history,
+            storage::branch_commands::fork_thread,
+            storage::branch_commands::get_active_branch,
+            storage::branch_commands::set_active_branch,
+            storage::archive_commands::export_thread,
+            storage::archive_commands::export_thread_markdown,
+            storage::archive_commands::import_thread,
+            storage::backup_commands::list_backups,
+            storage::backup_commands::create_backup,
+            storage::backup_commands::restore_backup,
+            storage::backup_commands::verify_storage,
+            storage::backup_commands::get_backup_settings,
+            storage::backup_commands::set_backup_settings,
+            storage::usage_commands::usage_report,
+            storage::usage_commands::get_usage_settings,
+            storage::usage_commands::set_usage_settings,
+            storage::usage_commands::check_budgets,
+            storage::todo_commands::read_todos,
+            storage::todo_commands::write_todos,
+            storage::todo_commands::list_todo_history,
             storage::message_diff_commands::add_message,
             storage::message_diff_commands::list_messages,
+            storage::message_diff_commands::update_message,
+            storage::message_diff_commands::delete_messages_after,
             storage::message_diff_commands::save_diff,
             storage::message_diff_commands::list_diffs,
             storage::message_diff_commands::clear_diffs,
+            diff::commands::export_diffs_as_patch,
+            diff::commands::squash_diffs,
+            diff::commands::list_diff_hunks,
+            diff::commands::review_diff_hunks,
             auth::commands::read_auth_config,
             auth::commands::write_auth_config,
+            auth::commands::auth_status,
+            auth::commands::unlock_auth,
+            auth::commands::lock_auth,
+            auth::commands::set_auth_passphrase,
+            auth::commands::set_auth_auto_lock,
             ui::commands::pick_folder,
             agent::commands::agent_read_file,
             agent::commands::agent_write_file,
             agent::commands::agent_delete_file,
-            agent::commands::agent_run_command
+            agent::commands::agent_run_command,
+            agent::commands::load_project_instructions,
+            git::commands::git_status,
+            git::commands::git_diff,
+            git::commands::git_stage,
+            git::commands::git_commit,
+            git::commands::git_list_branches,
+            git::commands::git_create_branch,
+            git::commands::git_switch_branch,
+            git::commands::git_log,
+            git::worktree_commands::create_thread_worktree,
+            git::worktree_commands::merge_thread_worktree,
+            git::worktree_commands::discard_thread_worktree
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
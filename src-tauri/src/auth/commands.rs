@@ -1,25 +1,279 @@
+use std::collections::HashMap;
+use std::mem;
+use std::path::Path;
+
 use tauri::AppHandle;
+use zeroize::Zeroizing;
 
-use crate::core::models::AuthConfig;
+use crate::auth::passphrase::{
+    check_value, derive_key, end_session, is_unlocked, new_kdf_params, set_session_auto_lock,
+    start_session, verify_passphrase,
+};
+use crate::auth::sealed::{
+    load_file_key, open, read_sealed, remove_file_key, seal, write_sealed, SealedSecrets,
+};
+use crate::auth::secrets::{
+    delete_secret, ensure_unlocked, get_secret, preferred_backend, set_secret, write_backend,
+};
+use crate::core::constants::MIN_PASSPHRASE_CHARS;
+use crate::core::models::{AuthConfig, AuthStatus, SecretBackend, StoredApiKey, StoredAuthConfig};
+use crate::core::time::now_ms;
 use crate::storage::io::{read_json_file, write_json_file};
 use crate::storage::paths::auth_config_path;
 
+fn auth_dir(path: &Path) -> Result<&Path, String> {
+    path.parent()
+        .ok_or_else(|| "Missing parent folder for auth config".to_string())
+}
+
+fn load_stored_auth(path: &Path) -> Result<StoredAuthConfig, String> {
+    if !path.exists() {
+        return Ok(StoredAuthConfig::default());
+    }
+    let mut stored = read_json_file::<StoredAuthConfig>(path).map_err(|e| {
+        format!(
+            "{} is damaged and was not loaded: {e}. Fix or remove it to continue.",
+            path.display()
+        )
+    })?;
+    if stored.api_keys.is_empty() {
+        return Ok(stored);
+    }
+
+    let dir = auth_dir(path)?;
+    let backend = write_backend(dir)?;
+    let ts = now_ms();
+    for (provider, key) in mem::take(&mut stored.api_keys) {
+        if key.is_empty() {
+            continue;
+        }
+        set_secret(dir, backend, &provider, &key)?;
+        stored.providers.insert(
+            provider,
+            StoredApiKey {
+                backend,
+                updated_at_ms: ts,
+            },
+        );
+    }
+    write_json_file(path, &stored)?;
+    Ok(stored)
+}
+
 #[tauri::command]
 pub fn read_auth_config(app: AppHandle) -> Result<AuthConfig, String> {
     let path = auth_config_path(&app)?;
-    if !path.exists() {
-        return Ok(AuthConfig::default());
-    }
+    let stored = load_stored_auth(&path)?;
+    let dir = auth_dir(&path)?;
 
-    match read_json_file::<AuthConfig>(&path) {
-        Ok(config) => Ok(config),
-        Err(_) => Ok(AuthConfig::default()),
+    let mut api_keys = HashMap::new();
+    for (provider, entry) in &stored.providers {
+        if let Some(key) = get_secret(dir, entry.backend, provider)? {
+            api_keys.insert(provider.clone(), key);
+        }
     }
+    Ok(AuthConfig { api_keys })
 }
 
 #[tauri::command]
 pub fn write_auth_config(app: AppHandle, input: AuthConfig) -> Result<AuthConfig, String> {
     let path = auth_config_path(&app)?;
-    write_json_file(&path, &input)?;
-    Ok(input)
+    let mut stored = load_stored_auth(&path)?;
+    let dir = auth_dir(&path)?;
+    ensure_unlocked(dir)?;
+    let backend = write_backend(dir)?;
+    let ts = now_ms();
+
+    let api_keys = input
+        .api_keys
+        .into_iter()
+        .filter(|(_, key)| !key.is_empty())
+        .collect::<HashMap<_, _>>();
+
+    let removed = stored
+        .providers
+        .keys()
+        .filter(|provider| !api_keys.contains_key(*provider))
+        .cloned()
+        .collect::<Vec<_>>();
+    for provider in removed {
+        if let Some(entry) = stored.providers.remove(&provider) {
+            delete_secret(dir, entry.backend, &provider)?;
+        }
+    }
+
+    for (provider, key) in &api_keys {
+        if let Some(entry) = stored.providers.get(provider) {
+            if get_secret(dir, entry.backend, provider)?.as_deref() == Some(key) {
+                continue;
+            }
+        }
+        set_secret(dir, backend, provider, key)?;
+        let previous = stored.providers.insert(
+            provider.clone(),
+            StoredApiKey {
+                backend,
+                updated_at_ms: ts,
+            },
+        );
+        if let Some(previous) = previous.filter(|p| p.backend != backend) {
+            delete_secret(dir, previous.backend, provider)?;
+        }
+    }
+
+    write_json_file(&path, &stored)?;
+    Ok(AuthConfig { api_keys })
+}
+
+fn auth_status_in(dir: &Path) -> Result<AuthStatus, String> {
+    let sealed = read_sealed(dir)?;
+    let passphrase_set = sealed.kdf.is_some();
+    Ok(AuthStatus {
+        backend: if passphrase_set {
+            SecretBackend::File
+        } else {
+            preferred_backend()
+        },
+        passphrase_set,
+        locked: passphrase_set && !is_unlocked(dir),
+        auto_lock_ms: sealed.auto_lock_ms,
+    })
+}
+
+#[tauri::command]
+pub fn auth_status(app: AppHandle) -> Result<AuthStatus, String> {
+    let path = auth_config_path(&app)?;
+    auth_status_in(auth_dir(&path)?)
+}
+
+#[tauri::command]
+pub fn unlock_auth(app: AppHandle, passphrase: String) -> Result<AuthStatus, String> {
+    let passphrase = Zeroizing::new(passphrase);
+    let path = auth_config_path(&app)?;
+    let dir = auth_dir(&path)?;
+    let sealed = read_sealed(dir)?;
+    let key = verify_passphrase(&sealed, &passphrase)?;
+    start_session(dir, key, sealed.auto_lock_ms);
+    auth_status_in(dir)
+}
+
+#[tauri::command]
+pub fn lock_auth(app: AppHandle) -> Result<AuthStatus, String> {
+    end_session();
+    let path = auth_config_path(&app)?;
+    auth_status_in(auth_dir(&path)?)
+}
+
+#[tauri::command]
+pub fn set_auth_passphrase(
+    app: AppHandle,
+    passphrase: Option<String>,
+    current_passphrase: Option<String>,
+) -> Result<AuthStatus, String> {
+    let passphrase = passphrase.map(Zeroizing::new);
+    let current_passphrase = current_passphrase.map(Zeroizing::new);
+    if let Some(passphrase) = &passphrase {
+        if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+            return Err(format!(
+                "Passphrase must be at least {MIN_PASSPHRASE_CHARS} characters"
+            ));
+        }
+    }
+
+    let path = auth_config_path(&app)?;
+    let mut stored = load_stored_auth(&path)?;
+    let dir = auth_dir(&path)?;
+    let sealed = read_sealed(dir)?;
+    if sealed.kdf.is_none() && passphrase.is_none() {
+        return auth_status_in(dir);
+    }
+    let old_key = match (&sealed.kdf, &current_passphrase) {
+        (Some(_), Some(current)) => verify_passphrase(&sealed, current)?,
+        (Some(_), None) => {
+            return Err("Enter the current passphrase to change it".to_string());
+        }
+        (None, _) => load_file_key(dir)?,
+    };
+
+    let mut secrets = Vec::new();
+    for (provider, entry) in &stored.providers {
+        let secret = match entry.backend {
+            SecretBackend::File => match sealed.entries.get(provider) {
+                Some(value) => open(&old_key, provider, value)?.ok_or_else(|| {
+                    format!("Could not decrypt the stored API key for {provider}")
+                })?,
+                None => continue,
+            },
+            SecretBackend::Keyring => match get_secret(dir, entry.backend, provider)? {
+                Some(secret) => secret,
+                None => continue,
+            },
+        };
+        secrets.push((provider.clone(), Zeroizing::new(secret)));
+    }
+
+    let (kdf, new_key, target) = match &passphrase {
+        Some(passphrase) => {
+            let kdf = new_kdf_params();
+            let key = derive_key(passphrase, &kdf)?;
+            (Some(kdf), key, SecretBackend::File)
+        }
+        None => (None, load_file_key(dir)?, preferred_backend()),
+    };
+    let mut next = SealedSecrets {
+        kdf,
+        check: None,
+        auto_lock_ms: sealed.auto_lock_ms,
+        ..SealedSecrets::default()
+    };
+    if next.kdf.is_some() {
+        next.check = Some(check_value(&new_key)?);
+    }
+    for (provider, secret) in &secrets {
+        match target {
+            SecretBackend::File => {
+                next.entries
+                    .insert(provider.clone(), seal(&new_key, provider, secret)?);
+            }
+            SecretBackend::Keyring => set_secret(dir, target, provider, secret)?,
+        }
+    }
+    write_sealed(dir, &next)?;
+
+    let mut left_in_keyring = Vec::new();
+    for (provider, _) in &secrets {
+        if let Some(entry) = stored.providers.get_mut(provider) {
+            if entry.backend == SecretBackend::Keyring && target == SecretBackend::File {
+                left_in_keyring.push(provider.clone());
+            }
+            entry.backend = target;
+        }
+    }
+    stored
+        .providers
+        .retain(|provider, _| secrets.iter().any(|(p, _)| p == provider));
+    write_json_file(&path, &stored)?;
+    // Only once `auth.json` points at the file are the keyring copies removed.
+    for provider in &left_in_keyring {
+        delete_secret(dir, SecretBackend::Keyring, provider)?;
+    }
+
+    if passphrase.is_some() {
+        remove_file_key(dir)?;
+        start_session(dir, new_key, next.auto_lock_ms);
+    } else {
+        end_session();
+    }
+    auth_status_in(dir)
+}
+
+#[tauri::command]
+pub fn set_auth_auto_lock(app: AppHandle, auto_lock_ms: u64) -> Result<AuthStatus, String> {
+    let path = auth_config_path(&app)?;
+    let dir = auth_dir(&path)?;
+    let mut sealed = read_sealed(dir)?;
+    sealed.auto_lock_ms = auto_lock_ms;
+    write_sealed(dir, &sealed)?;
+    set_session_auto_lock(auto_lock_ms);
+    auth_status_in(dir)
 }
@@ -1 +1,4 @@
 pub mod commands;
+pub mod passphrase;
+pub mod sealed;
+pub mod secrets;
@@ -0,0 +1,206 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::OsRng;
+
+use crate::auth::sealed::{
+    from_hex, open, seal, to_hex, KdfParams, SealedSecrets, SealedValue, SecretKey,
+};
+
+const CHECK_AAD: &str = "kodit-passphrase-check";
+const CHECK_TEXT: &str = "kodit";
+const SALT_LEN: usize = 16;
+
+struct Session {
+    dir: PathBuf,
+    key: SecretKey,
+    auto_lock_ms: u64,
+    last_used: Instant,
+}
+
+impl Session {
+    fn expired(&self) -> bool {
+        self.auto_lock_ms > 0
+            && self.last_used.elapsed() >= Duration::from_millis(self.auto_lock_ms)
+    }
+}
+
+static SESSION: Mutex<Option<Session>> = Mutex::new(None);
+
+fn session() -> MutexGuard<'static, Option<Session>> {
+    SESSION.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+pub fn new_kdf_params() -> KdfParams {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    KdfParams {
+        salt: to_hex(&salt),
+        memory_kib: Params::DEFAULT_M_COST,
+        iterations: Params::DEFAULT_T_COST,
+        parallelism: Params::DEFAULT_P_COST,
+    }
+}
+
+pub fn derive_key(passphrase: &str, params: &KdfParams) -> Result<SecretKey, String> {
+    let salt = from_hex(&params.salt)?;
+    let params = Params::new(
+        params.memory_kib,
+        params.iterations,
+        params.parallelism,
+        Some(32),
+    )
+    .map_err(|e| format!("Invalid key derivation settings: {e}"))?;
+    let mut key = SecretKey::default();
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), &salt, key.as_mut_slice())
+        .map_err(|e| format!("Could not derive a key from the passphrase: {e}"))?;
+    Ok(key)
+}
+
+pub fn check_value(key: &SecretKey) -> Result<SealedValue, String> {
+    seal(key, CHECK_AAD, CHECK_TEXT)
+}
+
+pub fn verify_passphrase(sealed: &SealedSecrets, passphrase: &str) -> Result<SecretKey, String> {
+    let (Some(kdf), Some(check)) = (&sealed.kdf, &sealed.check) else {
+        return Err("No passphrase is set for stored API keys".to_string());
+    };
+    let key = derive_key(passphrase, kdf)?;
+    match open(&key, CHECK_AAD, check)? {
+        Some(text) if text == CHECK_TEXT => Ok(key),
+        _ => Err("Wrong passphrase".to_string()),
+    }
+}
+
+pub fn start_session(dir: &Path, key: SecretKey, auto_lock_ms: u64) {
+    *session() = Some(Session {
+        dir: dir.to_path_buf(),
+        key,
+        auto_lock_ms,
+        last_used: Instant::now(),
+    });
+}
+
+pub fn end_session() {
+    session().take();
+}
+
+pub fn set_session_auto_lock(auto_lock_ms: u64) {
+    if let Some(active) = session().as_mut() {
+        active.auto_lock_ms = auto_lock_ms;
+    }
+}
+
+pub fn session_key(dir: &Path) -> Result<SecretKey, String> {
+    let mut guard = session();
+    if guard.as_ref().is_some_and(Session::expired) {
+        guard.take();
+    }
+    match guard.as_mut().filter(|active| active.dir == dir) {
+        Some(active) => {
+            active.last_used = Instant::now();
+            Ok(active.key.clone())
+        }
+        None => Err("Stored API keys are locked. Unlock them with your passphrase.".to_string()),
+    }
+}
+
+pub fn is_unlocked(dir: &Path) -> bool {
+    session()
+        .as_ref()
+        .is_some_and(|active| active.dir == dir && !active.expired())
+}
+
+pub fn expire_session() {
+    let mut guard = session();
+    if guard.as_ref().is_some_and(Session::expired) {
+        guard.take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Small enough to keep the tests fast; real files use the argon2 defaults.
+    fn cheap_params(salt: &str) -> KdfParams {
+        KdfParams {
+            salt: salt.to_string(),
+            memory_kib: 64,
+            iterations: 1,
+            parallelism: 1,
+        }
+    }
+
+    fn sealed_with(passphrase: &str) -> SealedSecrets {
+        let kdf = cheap_params("00112233445566778899aabbccddeeff");
+        let key = derive_key(passphrase, &kdf).unwrap();
+        SealedSecrets {
+            check: Some(check_value(&key).unwrap()),
+            kdf: Some(kdf),
+            ..SealedSecrets::default()
+        }
+    }
+
+    #[test]
+    fn derived_keys_depend_on_passphrase_and_salt() {
+        let params = cheap_params("00112233445566778899aabbccddeeff");
+        let key = derive_key("correct horse", &params).unwrap();
+        assert_eq!(derive_key("correct horse", &params).unwrap(), key);
+        assert_ne!(derive_key("correct horsf", &params).unwrap(), key);
+        let other_salt = cheap_params("ffeeddccbbaa99887766554433221100");
+        assert_ne!(derive_key("correct horse", &other_salt).unwrap(), key);
+
+        assert!(derive_key("x", &cheap_params("zz")).is_err());
+        let mut too_little_memory = params.clone();
+        too_little_memory.memory_kib = 1;
+        assert!(derive_key("x", &too_little_memory).is_err());
+    }
+
+    #[test]
+    fn new_params_use_fresh_salts() {
+        let first = new_kdf_params();
+        assert_eq!(from_hex(&first.salt).unwrap().len(), SALT_LEN);
+        assert_ne!(new_kdf_params().salt, first.salt);
+    }
+
+    #[test]
+    fn verifies_against_the_check_value() {
+        let sealed = sealed_with("correct horse");
+        let key = verify_passphrase(&sealed, "correct horse").unwrap();
+        assert_eq!(
+            key,
+            derive_key("correct horse", sealed.kdf.as_ref().unwrap()).unwrap()
+        );
+        assert_eq!(
+            verify_passphrase(&sealed, "wrong horse").unwrap_err(),
+            "Wrong passphrase"
+        );
+        assert!(verify_passphrase(&SealedSecrets::default(), "correct horse").is_err());
+    }
+
+    // The session is process-wide, so every case that touches it lives here.
+    #[test]
+    fn session_unlocks_one_dir_until_it_expires() {
+        let dir = Path::new("/kodit-test/auth");
+        let key = SecretKey::new([7; 32]);
+
+        start_session(dir, key.clone(), 0);
+        assert!(is_unlocked(dir));
+        assert_eq!(session_key(dir).unwrap(), key);
+        assert!(session_key(Path::new("/kodit-test/other")).is_err());
+
+        set_session_auto_lock(1);
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(!is_unlocked(dir));
+        assert!(session_key(dir).is_err());
+
+        start_session(dir, key, 0);
+        end_session();
+        assert!(!is_unlocked(dir));
+    }
+}
@@ -0,0 +1,304 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::core::constants::{
+    DEFAULT_AUTO_LOCK_MS, SECRETS_FILE, SECRETS_FILE_VERSION, SECRETS_KEY_FILE,
+};
+use crate::storage::io::{read_json_file, write_json_file};
+
+pub type SecretKey = Zeroizing<[u8; 32]>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SealedValue {
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub salt: String,
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+fn default_auto_lock_ms() -> u64 {
+    DEFAULT_AUTO_LOCK_MS
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SealedSecrets {
+    pub version: u32,
+    #[serde(default)]
+    pub kdf: Option<KdfParams>,
+    #[serde(default)]
+    pub check: Option<SealedValue>,
+    #[serde(default = "default_auto_lock_ms")]
+    pub auto_lock_ms: u64,
+    pub entries: BTreeMap<String, SealedValue>,
+}
+
+impl Default for SealedSecrets {
+    fn default() -> Self {
+        Self {
+            version: SECRETS_FILE_VERSION,
+            kdf: None,
+            check: None,
+            auto_lock_ms: DEFAULT_AUTO_LOCK_MS,
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+pub fn from_hex(text: &str) -> Result<Vec<u8>, String> {
+    if !text.len().is_multiple_of(2) {
+        return Err("Invalid hex in secrets file".to_string());
+    }
+    text.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .ok_or_else(|| "Invalid hex in secrets file".to_string())
+        })
+        .collect()
+}
+
+pub fn load_file_key(dir: &Path) -> Result<SecretKey, String> {
+    let path = dir.join(SECRETS_KEY_FILE);
+    let mut key = SecretKey::default();
+    if path.exists() {
+        let mut bytes = fs::read(&path).map_err(|e| e.to_string())?;
+        if bytes.len() != key.len() {
+            return Err(format!("{} is not a valid key file", path.display()));
+        }
+        key.copy_from_slice(&bytes);
+        bytes.zeroize();
+        return Ok(key);
+    }
+
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    OsRng.fill_bytes(key.as_mut_slice());
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path).map_err(|e| e.to_string())?;
+    file.write_all(key.as_slice()).map_err(|e| e.to_string())?;
+    Ok(key)
+}
+
+pub fn remove_file_key(dir: &Path) -> Result<(), String> {
+    let path = dir.join(SECRETS_KEY_FILE);
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+pub fn read_sealed(dir: &Path) -> Result<SealedSecrets, String> {
+    let path = dir.join(SECRETS_FILE);
+    if !path.exists() {
+        return Ok(SealedSecrets::default());
+    }
+    let sealed = read_json_file::<SealedSecrets>(&path)
+        .map_err(|e| format!("{} is damaged: {e}", path.display()))?;
+    if sealed.version > SECRETS_FILE_VERSION {
+        return Err(format!(
+            "{} was written by a newer version of Kodit",
+            path.display()
+        ));
+    }
+    Ok(sealed)
+}
+
+pub fn write_sealed(dir: &Path, sealed: &SealedSecrets) -> Result<(), String> {
+    let mut sealed = sealed.clone();
+    sealed.version = SECRETS_FILE_VERSION;
+    write_json_file(&dir.join(SECRETS_FILE), &sealed)
+}
+
+fn cipher(key: &SecretKey) -> XChaCha20Poly1305 {
+    XChaCha20Poly1305::new(Key::from_slice(key.as_slice()))
+}
+
+pub fn seal(key: &SecretKey, aad: &str, plaintext: &str) -> Result<SealedValue, String> {
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher(key)
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext.as_bytes(),
+                aad: aad.as_bytes(),
+            },
+        )
+        .map_err(|_| format!("Could not encrypt {aad}"))?;
+    Ok(SealedValue {
+        nonce: to_hex(&nonce),
+        ciphertext: to_hex(&ciphertext),
+    })
+}
+
+pub fn open(key: &SecretKey, aad: &str, value: &SealedValue) -> Result<Option<String>, String> {
+    let nonce = from_hex(&value.nonce)?;
+    if nonce.len() != 24 {
+        return Err("Invalid nonce in secrets file".to_string());
+    }
+    let Ok(plaintext) = cipher(key).decrypt(
+        XNonce::from_slice(&nonce),
+        Payload {
+            msg: &from_hex(&value.ciphertext)?,
+            aad: aad.as_bytes(),
+        },
+    ) else {
+        return Ok(None);
+    };
+    String::from_utf8(plaintext)
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
+pub fn read_sealed_secret(
+    dir: &Path,
+    key: &SecretKey,
+    provider: &str,
+) -> Result<Option<String>, String> {
+    let sealed = read_sealed(dir)?;
+    let Some(value) = sealed.entries.get(provider) else {
+        return Ok(None);
+    };
+    open(key, provider, value)?
+        .map(Some)
+        .ok_or_else(|| format!("Could not decrypt the stored API key for {provider}"))
+}
+
+pub fn write_sealed_secret(
+    dir: &Path,
+    key: &SecretKey,
+    provider: &str,
+    secret: &str,
+) -> Result<(), String> {
+    let mut sealed = read_sealed(dir)?;
+    sealed
+        .entries
+        .insert(provider.to_string(), seal(key, provider, secret)?);
+    write_sealed(dir, &sealed)
+}
+
+pub fn delete_sealed_secret(dir: &Path, provider: &str) -> Result<(), String> {
+    let mut sealed = read_sealed(dir)?;
+    if sealed.entries.remove(provider).is_some() {
+        write_sealed(dir, &sealed)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(byte: u8) -> SecretKey {
+        Zeroizing::new([byte; 32])
+    }
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("kodit-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn hex_round_trips() {
+        assert_eq!(to_hex(&[0, 15, 255]), "000fff");
+        assert_eq!(from_hex("000fff").unwrap(), [0, 15, 255]);
+        assert!(from_hex("abc").is_err());
+        assert!(from_hex("zz").is_err());
+    }
+
+    #[test]
+    fn seal_and_open() {
+        let sealed = seal(&key(1), "openai", "sk-secret").unwrap();
+        assert_eq!(
+            open(&key(1), "openai", &sealed).unwrap().as_deref(),
+            Some("sk-secret")
+        );
+        // Wrong key, another provider's slot or a flipped byte all fail to open.
+        assert_eq!(open(&key(2), "openai", &sealed).unwrap(), None);
+        assert_eq!(open(&key(1), "gemini", &sealed).unwrap(), None);
+        let mut tampered = sealed.clone();
+        let flipped = if tampered.ciphertext.starts_with('0') {
+            "1"
+        } else {
+            "0"
+        };
+        tampered.ciphertext.replace_range(0..1, flipped);
+        assert_eq!(open(&key(1), "openai", &tampered).unwrap(), None);
+
+        // Each seal uses a fresh nonce.
+        let again = seal(&key(1), "openai", "sk-secret").unwrap();
+        assert_ne!(again.nonce, sealed.nonce);
+    }
+
+    #[test]
+    fn file_key_is_created_once() {
+        let dir = temp_dir("file-key");
+        let first = load_file_key(&dir).unwrap();
+        assert_eq!(load_file_key(&dir).unwrap(), first);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(dir.join(SECRETS_KEY_FILE))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        fs::write(dir.join(SECRETS_KEY_FILE), b"short").unwrap();
+        assert!(load_file_key(&dir).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn secrets_file_round_trips() {
+        let dir = temp_dir("sealed-secrets");
+        fs::create_dir_all(&dir).unwrap();
+        assert_eq!(read_sealed_secret(&dir, &key(3), "openai").unwrap(), None);
+
+        write_sealed_secret(&dir, &key(3), "openai", "sk-1").unwrap();
+        write_sealed_secret(&dir, &key(3), "gemini", "AIza").unwrap();
+        assert_eq!(
+            read_sealed_secret(&dir, &key(3), "openai")
+                .unwrap()
+                .as_deref(),
+            Some("sk-1")
+        );
+        assert!(read_sealed_secret(&dir, &key(4), "openai").is_err());
+
+        delete_sealed_secret(&dir, "openai").unwrap();
+        assert_eq!(read_sealed_secret(&dir, &key(3), "openai").unwrap(), None);
+        assert_eq!(
+            read_sealed_secret(&dir, &key(3), "gemini")
+                .unwrap()
+                .as_deref(),
+            Some("AIza")
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
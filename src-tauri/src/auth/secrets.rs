@@ -0,0 +1,94 @@
+use std::env;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use keyring::Entry;
+
+use crate::auth::passphrase::session_key;
+use crate::auth::sealed::{
+    delete_sealed_secret, load_file_key, read_sealed, read_sealed_secret, write_sealed_secret,
+    SecretKey,
+};
+use crate::core::constants::{KEYRING_SERVICE, SECRET_STORE_ENV};
+use crate::core::models::SecretBackend;
+
+fn keyring_entry(provider: &str) -> Result<Entry, String> {
+    Entry::new(KEYRING_SERVICE, provider).map_err(|e| e.to_string())
+}
+
+fn probe_keyring() -> SecretBackend {
+    let usable = keyring_entry("kodit-probe")
+        .is_ok_and(|entry| matches!(entry.get_password(), Ok(_) | Err(keyring::Error::NoEntry)));
+    if usable {
+        SecretBackend::Keyring
+    } else {
+        SecretBackend::File
+    }
+}
+
+pub fn preferred_backend() -> SecretBackend {
+    static BACKEND: OnceLock<SecretBackend> = OnceLock::new();
+    *BACKEND.get_or_init(|| match env::var(SECRET_STORE_ENV).as_deref() {
+        Ok("file") => SecretBackend::File,
+        _ => probe_keyring(),
+    })
+}
+
+pub fn write_backend(dir: &Path) -> Result<SecretBackend, String> {
+    if read_sealed(dir)?.kdf.is_some() {
+        Ok(SecretBackend::File)
+    } else {
+        Ok(preferred_backend())
+    }
+}
+
+fn file_key(dir: &Path) -> Result<SecretKey, String> {
+    if read_sealed(dir)?.kdf.is_some() {
+        session_key(dir)
+    } else {
+        load_file_key(dir)
+    }
+}
+
+pub fn ensure_unlocked(dir: &Path) -> Result<(), String> {
+    file_key(dir).map(|_| ())
+}
+
+pub fn get_secret(
+    dir: &Path,
+    backend: SecretBackend,
+    provider: &str,
+) -> Result<Option<String>, String> {
+    match backend {
+        SecretBackend::Keyring => match keyring_entry(provider)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(format!("Could not read the API key for {provider}: {e}")),
+        },
+        SecretBackend::File => read_sealed_secret(dir, &file_key(dir)?, provider),
+    }
+}
+
+pub fn set_secret(
+    dir: &Path,
+    backend: SecretBackend,
+    provider: &str,
+    secret: &str,
+) -> Result<(), String> {
+    match backend {
+        SecretBackend::Keyring => keyring_entry(provider)?
+            .set_password(secret)
+            .map_err(|e| format!("Could not store the API key for {provider}: {e}")),
+        SecretBackend::File => write_sealed_secret(dir, &file_key(dir)?, provider, secret),
+    }
+}
+
+pub fn delete_secret(dir: &Path, backend: SecretBackend, provider: &str) -> Result<(), String> {
+    match backend {
+        SecretBackend::Keyring => match keyring_entry(provider)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("Could not remove the API key for {provider}: {e}")),
+        },
+        SecretBackend::File => delete_sealed_secret(dir, provider),
+    }
+}
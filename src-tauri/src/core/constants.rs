@@ -3,6 +3,51 @@ pub const PROJECTS_DIR: &str = "projects";
 pub const THREADS_DIR: &str = "threads";
 pub const MESSAGES_DIR: &str = "messages";
 pub const DIFFS_DIR: &str = "diffs";
+pub const WORKTREES_DIR: &str = "worktrees";
 pub const MANIFEST_FILE: &str = "manifest.json";
-pub const SCHEMA_VERSION: u32 = 1;
+pub const DATABASE_FILE: &str = "kodit.db";
+pub const LEGACY_DIR: &str = "legacy";
+pub const LEGACY_JSON_DIRS: [&str; 3] = [PROJECTS_DIR, THREADS_DIR, MESSAGES_DIR];
+pub const BACKUPS_DIR: &str = "backups";
+pub const CORRUPT_DIR: &str = "corrupt";
+pub const QUARANTINE_LOG_FILE: &str = "quarantine.json";
+pub const TMP_FILE_MIN_AGE_SECS: u64 = 60;
+pub const STORAGE_LOCK_FILE: &str = "kodit.lock";
+pub const LOCKS_DIR: &str = "locks";
+pub const STORAGE_LOCK_TIMEOUT_MS: u64 = 30_000;
+pub const THREAD_LOCK_TIMEOUT_MS: u64 = 5_000;
+pub const LOCK_RETRY_INTERVAL_MS: u64 = 25;
+pub const BACKUP_SETTINGS_FILE: &str = "settings.json";
+pub const AUTO_BACKUP_LABEL: &str = "auto";
+pub const DEFAULT_BACKUP_KEEP: usize = 10;
+pub const DEFAULT_BACKUP_INTERVAL_MS: u64 = 24 * 60 * 60 * 1000;
+pub const BACKUP_CHECK_INTERVAL_SECS: u64 = 15 * 60;
+pub const SCHEMA_VERSION: u32 = 6;
 pub const AUTH_CONFIG_REL_PATH: &str = ".config/com.1jmdev.kodit/auth.json";
+pub const KEYRING_SERVICE: &str = "com.1jmdev.kodit";
+pub const SECRETS_FILE: &str = "secrets.json";
+pub const SECRETS_KEY_FILE: &str = "secrets.key";
+pub const SECRETS_FILE_VERSION: u32 = 2;
+pub const SECRET_STORE_ENV: &str = "KODIT_SECRET_STORE";
+pub const DEFAULT_AUTO_LOCK_MS: u64 = 15 * 60 * 1000;
+pub const AUTH_LOCK_CHECK_INTERVAL_SECS: u64 = 30;
+pub const MIN_PASSPHRASE_CHARS: usize = 8;
+pub const PROJECT_CONFIG_DIR: &str = ".kodit";
+pub const PROJECT_CONFIG_FILE: &str = "config.toml";
+pub const RULES_DIR: &str = "rules";
+pub const INSTRUCTION_FILE_NAME: &str = "AGENTS.md";
+pub const INSTRUCTION_SKIPPED_DIRS: [&str; 5] = ["node_modules", "target", "dist", "build", "vendor"];
+pub const MAX_INSTRUCTION_DEPTH: usize = 8;
+pub const MAX_INSTRUCTION_INCLUDE_DEPTH: usize = 5;
+pub const MAX_INSTRUCTION_FILE_BYTES: u64 = 256 * 1024;
+pub const DEFAULT_COMMAND_TIMEOUT_MS: u64 = 120_000;
+pub const DEFAULT_PATCH_CONTEXT_LINES: usize = 3;
+pub const WORKTREE_BRANCH_PREFIX: &str = "kodit/";
+pub const MESSAGE_PAGE_SIZE: usize = 100;
+pub const MAX_MESSAGE_PAGE_SIZE: usize = 1000;
+pub const TRASH_RETENTION_MS: u64 = 30 * 24 * 60 * 60 * 1000;
+pub const SEARCH_RESULT_LIMIT: usize = 50;
+pub const USAGE_SETTINGS_FILE: &str = "usage.json";
+pub const UNKNOWN_USAGE_KEY: &str = "unknown";
+pub const THREAD_ARCHIVE_FORMAT: &str = "kodit-thread";
+pub const THREAD_ARCHIVE_VERSION: u32 = 1;
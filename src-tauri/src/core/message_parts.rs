@@ -0,0 +1,137 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::core::models::{MessagePart, ToolCallStatus};
+
+const META_PREFIX: &str = "\n\n[KODIT_META]";
+const META_SUFFIX: &str = "[/KODIT_META]";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LegacyMeta {
+    version: u32,
+    #[serde(default)]
+    reasoning: Value,
+    #[serde(default)]
+    tool_calls: Value,
+}
+
+#[derive(Deserialize)]
+struct LegacyToolCall {
+    id: String,
+    name: String,
+    args: String,
+    status: ToolCallStatus,
+    #[serde(default)]
+    result: Value,
+}
+
+fn split_meta(content: &str) -> (&str, Option<LegacyMeta>) {
+    let Some(start) = content.rfind(META_PREFIX) else {
+        return (content, None);
+    };
+    let Some(raw) = content[start + META_PREFIX.len()..].strip_suffix(META_SUFFIX) else {
+        return (content, None);
+    };
+    match serde_json::from_str::<LegacyMeta>(raw) {
+        Ok(meta) if meta.version == 1 => (&content[..start], Some(meta)),
+        _ => (content, None),
+    }
+}
+
+pub fn parts_from_content(content: &str) -> Vec<MessagePart> {
+    let (text, meta) = split_meta(content);
+    let mut parts = Vec::new();
+
+    if let Some(meta) = meta {
+        if let Some(reasoning) = meta.reasoning.as_str().filter(|r| !r.trim().is_empty()) {
+            parts.push(MessagePart::Reasoning {
+                text: reasoning.to_string(),
+            });
+        }
+        // Like the frontend's decoder, a malformed tool call is dropped on
+        // its own rather than discarding the rest of the metadata.
+        let calls = match meta.tool_calls {
+            Value::Array(items) => items,
+            _ => Vec::new(),
+        };
+        for call in calls
+            .into_iter()
+            .filter_map(|item| serde_json::from_value::<LegacyToolCall>(item).ok())
+        {
+            let args = serde_json::from_str(&call.args).unwrap_or(Value::String(call.args));
+            parts.push(MessagePart::ToolCall {
+                id: call.id.clone(),
+                name: call.name,
+                args,
+            });
+            parts.push(MessagePart::ToolResult {
+                tool_call_id: call.id,
+                status: call.status,
+                output: call.result.as_str().map(str::to_string),
+            });
+        }
+    }
+
+    if !text.is_empty() {
+        parts.push(MessagePart::Text {
+            text: text.to_string(),
+        });
+    }
+    parts
+}
+
+pub fn parts_text(parts: &[MessagePart]) -> String {
+    parts
+        .iter()
+        .filter_map(|part| match part {
+            MessagePart::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_meta(text: &str, meta: &str) -> String {
+        format!("{text}{META_PREFIX}{meta}{META_SUFFIX}")
+    }
+
+    #[test]
+    fn plain_content_is_one_text_part() {
+        let parts = parts_from_content("hello");
+        assert!(matches!(parts.as_slice(), [MessagePart::Text { text }] if text == "hello"));
+    }
+
+    #[test]
+    fn skips_invalid_tool_calls_individually() {
+        let content = with_meta(
+            "done",
+            r#"{"version":1,"reasoning":"why","toolCalls":[
+                {"id":"a","name":"read","args":"{\"path\":\"x\"}","status":"completed","result":"ok"},
+                {"id":"b","name":"read","status":"completed"},
+                {"id":"c","name":"bash","args":"ls","status":"bogus"},
+                7
+            ]}"#,
+        );
+        let parts = parts_from_content(&content);
+        assert_eq!(parts.len(), 4);
+        assert!(matches!(&parts[0], MessagePart::Reasoning { text } if text == "why"));
+        assert!(matches!(&parts[1], MessagePart::ToolCall { id, args, .. }
+            if id == "a" && args["path"] == "x"));
+        assert!(
+            matches!(&parts[2], MessagePart::ToolResult { output: Some(out), .. } if out == "ok")
+        );
+        assert!(matches!(&parts[3], MessagePart::Text { text } if text == "done"));
+    }
+
+    #[test]
+    fn unknown_meta_version_stays_text() {
+        let content = with_meta("hi", r#"{"version":2}"#);
+        let parts = parts_from_content(&content);
+        assert!(matches!(parts.as_slice(), [MessagePart::Text { text }] if *text == content));
+    }
+}
@@ -1,3 +1,5 @@
 pub mod constants;
+pub mod message_parts;
 pub mod models;
 pub mod time;
+pub mod workspace;
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::core::constants::{DEFAULT_BACKUP_INTERVAL_MS, DEFAULT_BACKUP_KEEP};
+use crate::core::message_parts::parts_from_content;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -9,10 +12,13 @@ pub enum MessageRole {
     System,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum AgentMode {
     Build,
+    Plan,
+    Ask,
+    Review,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -31,6 +37,99 @@ pub struct ProjectRecord {
     pub workspace_path: String,
     pub created_at_ms: u64,
     pub updated_at_ms: u64,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub deleted_at_ms: Option<u64>,
+    #[serde(default)]
+    pub settings: ProjectSettings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectListing {
+    #[serde(flatten)]
+    pub project: ProjectRecord,
+    pub workspace_missing: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct ProjectSettings {
+    pub model: Option<String>,
+    pub provider: Option<String>,
+    pub mode: Option<AgentMode>,
+    pub command_timeout_ms: Option<u64>,
+    pub protected_paths: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    pub instructions: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectSettingsView {
+    pub stored: ProjectSettings,
+    pub workspace: Option<ProjectSettings>,
+    pub workspace_error: Option<String>,
+    pub effective: ProjectSettings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadWorktree {
+    pub path: String,
+    pub branch: String,
+    pub base_branch: Option<String>,
+    pub base_commit: String,
+    pub created_at_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoStatus {
+    Pending,
+    InProgress,
+    Completed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoPriority {
+    High,
+    Medium,
+    Low,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TodoItem {
+    pub id: String,
+    pub content: String,
+    pub status: TodoStatus,
+    #[serde(default)]
+    pub priority: Option<TodoPriority>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoChange {
+    Added,
+    StatusChanged,
+    Edited,
+    Removed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoEvent {
+    pub todo_id: String,
+    pub change: TodoChange,
+    pub content: String,
+    pub status: TodoStatus,
+    pub previous_status: Option<TodoStatus>,
+    pub at_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadForkOrigin {
+    pub thread_id: String,
+    pub message_id: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,9 +140,61 @@ pub struct ThreadRecord {
     pub mode: AgentMode,
     pub created_at_ms: u64,
     pub updated_at_ms: u64,
+    #[serde(default)]
+    pub worktree: Option<ThreadWorktree>,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub deleted_at_ms: Option<u64>,
+    #[serde(default)]
+    pub active_leaf_id: Option<String>,
+    #[serde(default)]
+    pub forked_from: Option<ThreadForkOrigin>,
+    #[serde(default)]
+    pub todos: Vec<TodoItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolCallStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MessagePart {
+    Text {
+        text: String,
+    },
+    Reasoning {
+        text: String,
+    },
+    ToolCall {
+        id: String,
+        name: String,
+        args: serde_json::Value,
+    },
+    ToolResult {
+        tool_call_id: String,
+        status: ToolCallStatus,
+        #[serde(default)]
+        output: Option<String>,
+    },
+    Attachment {
+        name: String,
+        mime_type: String,
+        #[serde(default)]
+        path: Option<String>,
+        #[serde(default)]
+        data: Option<String>,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "StoredMessageRecord")]
 pub struct MessageRecord {
     pub id: String,
     pub thread_id: String,
@@ -57,6 +208,50 @@ pub struct MessageRecord {
     pub created_at_ms: u64,
     pub updated_at_ms: u64,
     pub sequence: u64,
+    pub parts: Vec<MessagePart>,
+}
+
+#[derive(Deserialize)]
+struct StoredMessageRecord {
+    id: String,
+    thread_id: String,
+    role: MessageRole,
+    content: String,
+    model: Option<String>,
+    provider: Option<String>,
+    mode: AgentMode,
+    tokens: TokenUsage,
+    parent_id: Option<String>,
+    created_at_ms: u64,
+    updated_at_ms: u64,
+    sequence: u64,
+    #[serde(default)]
+    parts: Vec<MessagePart>,
+}
+
+impl From<StoredMessageRecord> for MessageRecord {
+    fn from(stored: StoredMessageRecord) -> Self {
+        let parts = if stored.parts.is_empty() {
+            parts_from_content(&stored.content)
+        } else {
+            stored.parts
+        };
+        Self {
+            id: stored.id,
+            thread_id: stored.thread_id,
+            role: stored.role,
+            content: stored.content,
+            model: stored.model,
+            provider: stored.provider,
+            mode: stored.mode,
+            tokens: stored.tokens,
+            parent_id: stored.parent_id,
+            created_at_ms: stored.created_at_ms,
+            updated_at_ms: stored.updated_at_ms,
+            sequence: stored.sequence,
+            parts,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -83,6 +278,8 @@ pub struct DiffRecord {
     pub summary: Option<String>,
     pub created_at_ms: u64,
     pub files: Vec<FileSnapshotChange>,
+    #[serde(default)]
+    pub hunk_reviews: Vec<HunkReview>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -105,6 +302,7 @@ pub struct ThreadUpsertInput {
     pub project_id: String,
     pub title: Option<String>,
     pub mode: Option<AgentMode>,
+    pub worktree: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -119,6 +317,34 @@ pub struct MessageCreateInput {
     pub tokens: Option<TokenUsage>,
     pub parent_id: Option<String>,
     pub created_at_ms: Option<u64>,
+    pub parts: Option<Vec<MessagePart>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageUpdateInput {
+    pub thread_id: String,
+    pub message_id: String,
+    pub content: Option<String>,
+    pub tokens: Option<TokenUsage>,
+    pub model: Option<String>,
+    pub provider: Option<String>,
+    pub parts: Option<Vec<MessagePart>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkedDiffAction {
+    #[default]
+    Detach,
+    Revert,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedMessages {
+    pub thread_id: String,
+    pub message_ids: Vec<String>,
+    pub detached_diff_ids: Vec<String>,
+    pub reverted_diff_ids: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -147,3 +373,271 @@ pub struct StorageInfo {
 pub struct AuthConfig {
     pub api_keys: HashMap<String, String>,
 }
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretBackend {
+    Keyring,
+    File,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredApiKey {
+    pub backend: SecretBackend,
+    pub updated_at_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct StoredAuthConfig {
+    #[serde(default)]
+    pub providers: BTreeMap<String, StoredApiKey>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub api_keys: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthStatus {
+    pub backend: SecretBackend,
+    pub passphrase_set: bool,
+    pub locked: bool,
+    pub auto_lock_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadNetDiff {
+    pub thread_id: String,
+    pub source_diff_count: usize,
+    pub files: Vec<FileSnapshotChange>,
+    pub compacted: Vec<DiffRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HunkReviewStatus {
+    Accepted,
+    Rejected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HunkReview {
+    pub hunk_id: String,
+    pub status: HunkReviewStatus,
+    pub reviewed_at_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffHunk {
+    pub id: String,
+    pub file_path: String,
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub text: String,
+    pub status: Option<HunkReviewStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HunkReviewInput {
+    pub thread_id: String,
+    pub diff_id: String,
+    pub accepted: Vec<String>,
+    pub rejected: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashListing {
+    pub projects: Vec<ProjectRecord>,
+    pub threads: Vec<ThreadRecord>,
+    pub retention_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchHitKind {
+    Message,
+    Thread,
+    Diff,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub kind: SearchHitKind,
+    pub project_id: String,
+    pub thread_id: String,
+    pub thread_title: String,
+    pub message_id: Option<String>,
+    pub diff_id: Option<String>,
+    pub snippet: String,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchMessage {
+    pub message: MessageRecord,
+    pub sibling_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadBranch {
+    pub thread_id: String,
+    pub active_leaf_id: Option<String>,
+    pub messages: Vec<BranchMessage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadArchive {
+    pub format: String,
+    pub version: u32,
+    pub exported_at_ms: u64,
+    pub thread: ThreadRecord,
+    pub messages: Vec<MessageRecord>,
+    pub diffs: Vec<DiffRecord>,
+    #[serde(default)]
+    pub todo_history: Vec<TodoEvent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub id: String,
+    pub label: String,
+    pub created_at_ms: u64,
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupSettings {
+    pub keep: usize,
+    pub interval_ms: u64,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            keep: DEFAULT_BACKUP_KEEP,
+            interval_ms: DEFAULT_BACKUP_INTERVAL_MS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnreadableFile {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageIntegrityReport {
+    pub ok: bool,
+    pub database_errors: Vec<String>,
+    pub unreadable_files: Vec<UnreadableFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantinedFile {
+    pub original_path: String,
+    pub quarantined_path: String,
+    pub error: String,
+    pub quarantined_at_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageHealth {
+    pub quarantined: Vec<QuarantinedFile>,
+    pub unreadable_rows: Vec<String>,
+    pub recovered_tmp_files: Vec<String>,
+    pub removed_tmp_files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub model: String,
+    #[serde(default)]
+    pub provider: Option<String>,
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+    #[serde(default)]
+    pub reasoning_per_mtok: f64,
+    #[serde(default)]
+    pub cache_read_per_mtok: f64,
+    #[serde(default)]
+    pub cache_write_per_mtok: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BudgetScope {
+    Global,
+    Project { project_id: String },
+    Thread { thread_id: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetPeriod {
+    Day,
+    Month,
+    Total,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetAction {
+    Warn,
+    Block,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageBudget {
+    pub id: String,
+    pub scope: BudgetScope,
+    pub period: BudgetPeriod,
+    pub limit_usd: f64,
+    pub action: BudgetAction,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageSettings {
+    #[serde(default)]
+    pub prices: Vec<ModelPrice>,
+    #[serde(default)]
+    pub budgets: Vec<UsageBudget>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageGroupBy {
+    Thread,
+    Project,
+    Model,
+    Provider,
+    Day,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageTotals {
+    pub tokens: TokenUsage,
+    pub cost_usd: f64,
+    pub message_count: u64,
+    pub unpriced_message_count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageGroup {
+    pub key: String,
+    pub totals: UsageTotals,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageReport {
+    pub group_by: UsageGroupBy,
+    pub groups: Vec<UsageGroup>,
+    pub total: UsageTotals,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetStatus {
+    pub budget: UsageBudget,
+    pub spent_usd: f64,
+    pub exceeded: bool,
+}
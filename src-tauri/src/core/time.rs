@@ -6,3 +6,44 @@ pub fn now_ms() -> u64 {
         .map(|d| d.as_millis() as u64)
         .unwrap_or(0)
 }
+
+const MS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+pub fn utc_date(ts_ms: u64) -> String {
+    let (year, month, day) = civil_from_days((ts_ms / MS_PER_DAY) as i64);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+pub fn start_of_utc_day(ts_ms: u64) -> u64 {
+    ts_ms - ts_ms % MS_PER_DAY
+}
+
+pub fn start_of_utc_month(ts_ms: u64) -> u64 {
+    let (year, month, _) = civil_from_days((ts_ms / MS_PER_DAY) as i64);
+    days_from_civil(year, month, 1) as u64 * MS_PER_DAY
+}
@@ -0,0 +1,32 @@
+use std::fs;
+use std::path::PathBuf;
+
+pub fn canonicalize_workspace(workspace_path: &str) -> Result<PathBuf, String> {
+    let workspace = PathBuf::from(workspace_path);
+    if !workspace.is_absolute() {
+        return Err("Workspace path must be absolute".to_string());
+    }
+    if !workspace.exists() {
+        return Err("Workspace path does not exist".to_string());
+    }
+    fs::canonicalize(workspace).map_err(|e| e.to_string())
+}
+
+pub fn normalize_workspace_path(workspace_path: &str) -> Result<String, String> {
+    let trimmed = workspace_path.trim();
+    if trimmed.is_empty() {
+        return Err("Workspace path is empty".to_string());
+    }
+    let canonical = canonicalize_workspace(trimmed)?;
+    if !canonical.is_dir() {
+        return Err("Workspace path is not a directory".to_string());
+    }
+
+    let path = canonical.to_string_lossy().to_string();
+    // Windows canonical paths carry a verbatim prefix that other tools and
+    // users do not expect; drop it unless the path is a network share.
+    match path.strip_prefix(r"\\?\") {
+        Some(rest) if !rest.starts_with("UNC\\") => Ok(rest.to_string()),
+        _ => Ok(path),
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use rusqlite::{Connection, TransactionBehavior};
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use crate::agent::paths::resolve_path_in_workspace;
+use crate::core::constants::DEFAULT_PATCH_CONTEXT_LINES;
+use crate::core::models::{
+    DiffHunk, DiffRecord, HunkReview, HunkReviewInput, HunkReviewStatus, ThreadNetDiff,
+};
+use crate::core::time::now_ms;
+use crate::core::workspace::canonicalize_workspace;
+use crate::diff::hunks::{apply_hunk_selection, diff_hunks, rejected_hunk_ids};
+use crate::diff::patch::render_patch;
+use crate::diff::revert::write_file_state;
+use crate::diff::squash::squash_file_changes;
+use crate::git::worktree::thread_workspace_path;
+use crate::storage::db::{open_db, require_project, require_thread};
+use crate::storage::io::{
+    ensure_storage_ready, read_json_file, read_thread_diffs, write_json_file,
+};
+use crate::storage::lock::lock_thread;
+use crate::storage::paths::{diff_file, storage_root};
+use crate::storage::search::{index_diff, unindex_diff};
+
+#[tauri::command]
+pub fn export_diffs_as_patch(
+    app: AppHandle,
+    thread_id: String,
+    since: Option<u64>,
+    context_lines: Option<usize>,
+) -> Result<String, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let conn = open_db(&root)?;
+    let thread = require_thread(&conn, &thread_id)?;
+    let project = require_project(&conn, &thread.project_id)?;
+
+    let diffs = read_thread_diffs(&root, &thread.id)?
+        .into_iter()
+        .filter(|d| since.is_none_or(|since| d.created_at_ms >= since))
+        .collect::<Vec<_>>();
+    let changes = squash_file_changes(&diffs, &thread_workspace_path(&project, &thread));
+    Ok(render_patch(
+        &changes,
+        context_lines.unwrap_or(DEFAULT_PATCH_CONTEXT_LINES),
+    ))
+}
+
+#[tauri::command]
+pub fn squash_diffs(
+    app: AppHandle,
+    thread_id: String,
+    compact: Option<bool>,
+) -> Result<ThreadNetDiff, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let _thread_lock = lock_thread(&root, &thread_id)?;
+    let mut conn = open_db(&root)?;
+    let thread = require_thread(&conn, &thread_id)?;
+    let project = require_project(&conn, &thread.project_id)?;
+    let workspace_path = thread_workspace_path(&project, &thread);
+
+    let diffs = read_thread_diffs(&root, &thread.id)?;
+    let mut net = ThreadNetDiff {
+        thread_id: thread.id.clone(),
+        source_diff_count: diffs.len(),
+        files: squash_file_changes(&diffs, &workspace_path),
+        compacted: Vec::new(),
+    };
+
+    if !compact.unwrap_or(false) {
+        return Ok(net);
+    }
+
+    // Each message keeps its own record so the chat can still show what it changed.
+    let chains = diffs
+        .chunk_by(|a, b| a.message_id == b.message_id)
+        .filter(|chain| chain.len() > 1)
+        .collect::<Vec<_>>();
+    if chains.is_empty() {
+        return Ok(net);
+    }
+
+    let mut squashed = Vec::new();
+    for chain in &chains {
+        let files = squash_file_changes(chain, &workspace_path);
+        if files.is_empty() {
+            continue;
+        }
+        let last = &chain[chain.len() - 1];
+        squashed.push(DiffRecord {
+            id: Uuid::new_v4().to_string(),
+            thread_id: thread.id.clone(),
+            message_id: last.message_id.clone(),
+            summary: Some(format!("Squashed {} diffs", chain.len())),
+            created_at_ms: last.created_at_ms,
+            files,
+            hunk_reviews: Vec::new(),
+        });
+    }
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let written = write_squashed_diffs(&root, &tx, &squashed, &chains)
+        .and_then(|()| tx.commit().map_err(|e| e.to_string()));
+    if let Err(error) = written {
+        for diff in &squashed {
+            let _ = fs::remove_file(diff_file(&root, &thread.id, &diff.id));
+        }
+        return Err(error);
+    }
+
+    for diff in chains.iter().flat_map(|chain| chain.iter()) {
+        let path = diff_file(&root, &thread.id, &diff.id);
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| e.to_string())?;
+        }
+    }
+
+    net.compacted = squashed;
+    Ok(net)
+}
+
+fn write_squashed_diffs(
+    root: &Path,
+    conn: &Connection,
+    squashed: &[DiffRecord],
+    chains: &[&[DiffRecord]],
+) -> Result<(), String> {
+    for diff in squashed {
+        write_json_file(&diff_file(root, &diff.thread_id, &diff.id), diff)?;
+        index_diff(conn, diff)?;
+    }
+    for diff in chains.iter().flat_map(|chain| chain.iter()) {
+        unindex_diff(conn, &diff.id)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_diff_hunks(
+    app: AppHandle,
+    thread_id: String,
+    diff_id: String,
+) -> Result<Vec<DiffHunk>, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let conn = open_db(&root)?;
+    let _thread = require_thread(&conn, &thread_id)?;
+
+    let path = diff_file(&root, &thread_id, &diff_id);
+    if !path.exists() {
+        return Err(format!("Diff not found: {diff_id}"));
+    }
+    let diff = read_json_file::<DiffRecord>(&path)?;
+    Ok(diff_hunks(&diff))
+}
+
+#[tauri::command]
+pub fn review_diff_hunks(app: AppHandle, input: HunkReviewInput) -> Result<DiffRecord, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let _thread_lock = lock_thread(&root, &input.thread_id)?;
+    let conn = open_db(&root)?;
+    let thread = require_thread(&conn, &input.thread_id)?;
+    let project = require_project(&conn, &thread.project_id)?;
+
+    let path = diff_file(&root, &thread.id, &input.diff_id);
+    if !path.exists() {
+        return Err(format!("Diff not found: {}", input.diff_id));
+    }
+    let mut diff = read_json_file::<DiffRecord>(&path)?;
+
+    let known = diff_hunks(&diff)
+        .into_iter()
+        .map(|h| h.id)
+        .collect::<HashSet<_>>();
+    for id in input.accepted.iter().chain(&input.rejected) {
+        if !known.contains(id) {
+            return Err(format!("Hunk not found: {id}"));
+        }
+    }
+    if let Some(id) = input.accepted.iter().find(|id| input.rejected.contains(id)) {
+        return Err(format!("Hunk cannot be both accepted and rejected: {id}"));
+    }
+
+    let previously_rejected = rejected_hunk_ids(&diff);
+    let ts = now_ms();
+    diff.hunk_reviews
+        .retain(|r| !input.accepted.contains(&r.hunk_id) && !input.rejected.contains(&r.hunk_id));
+    for (ids, status) in [
+        (&input.accepted, HunkReviewStatus::Accepted),
+        (&input.rejected, HunkReviewStatus::Rejected),
+    ] {
+        for id in ids {
+            diff.hunk_reviews.push(HunkReview {
+                hunk_id: id.clone(),
+                status: status.clone(),
+                reviewed_at_ms: ts,
+            });
+        }
+    }
+    let now_rejected = rejected_hunk_ids(&diff);
+
+    // Check every file before touching any of them so a stale file does not
+    // leave the workspace half reverted.
+    let workspace_root = canonicalize_workspace(&thread_workspace_path(&project, &thread))?;
+    let mut writes = Vec::new();
+    for (file_index, change) in diff.files.iter().enumerate() {
+        let expected = apply_hunk_selection(file_index, change, &previously_rejected);
+        let desired = apply_hunk_selection(file_index, change, &now_rejected);
+        if expected == desired {
+            continue;
+        }
+
+        let resolved = resolve_path_in_workspace(&workspace_root, &change.file_path, true)?;
+        let current = if resolved.is_file() {
+            Some(fs::read_to_string(&resolved).map_err(|e| e.to_string())?)
+        } else {
+            None
+        };
+        if current != expected {
+            return Err(format!(
+                "File has changed since the diff was recorded: {}",
+                change.file_path
+            ));
+        }
+        writes.push((resolved, desired));
+    }
+
+    for (resolved, desired) in writes {
+        write_file_state(&resolved, desired.as_deref())?;
+    }
+
+    write_json_file(&path, &diff)?;
+    Ok(diff)
+}
@@ -0,0 +1,207 @@
+use std::collections::{HashMap, HashSet};
+
+use similar::{DiffTag, TextDiff};
+
+use crate::core::constants::DEFAULT_PATCH_CONTEXT_LINES;
+use crate::core::models::{
+    DiffHunk, DiffRecord, FileChangeType, FileSnapshotChange, HunkReviewStatus,
+};
+
+pub fn hunk_id(file_index: usize, hunk_index: usize) -> String {
+    format!("{file_index}:{hunk_index}")
+}
+
+pub fn diff_hunks(diff: &DiffRecord) -> Vec<DiffHunk> {
+    let statuses = diff
+        .hunk_reviews
+        .iter()
+        .map(|r| (r.hunk_id.as_str(), r.status.clone()))
+        .collect::<HashMap<_, _>>();
+
+    let mut hunks = Vec::new();
+    for (file_index, change) in diff.files.iter().enumerate() {
+        let old_content = change.old_content.as_deref().unwrap_or("");
+        let new_content = change.new_content.as_deref().unwrap_or("");
+        let text_diff = TextDiff::from_lines(old_content, new_content);
+        let mut unified = text_diff.unified_diff();
+        unified.context_radius(DEFAULT_PATCH_CONTEXT_LINES);
+
+        let groups = text_diff.grouped_ops(DEFAULT_PATCH_CONTEXT_LINES);
+        for (hunk_index, (group, hunk)) in groups.iter().zip(unified.iter_hunks()).enumerate() {
+            let (Some(first), Some(last)) = (group.first(), group.last()) else {
+                continue;
+            };
+            let id = hunk_id(file_index, hunk_index);
+            hunks.push(DiffHunk {
+                status: statuses.get(id.as_str()).cloned(),
+                id,
+                file_path: change.file_path.clone(),
+                old_start: first.old_range().start + 1,
+                old_lines: last.old_range().end - first.old_range().start,
+                new_start: first.new_range().start + 1,
+                new_lines: last.new_range().end - first.new_range().start,
+                text: hunk.to_string(),
+            });
+        }
+    }
+    hunks
+}
+
+pub fn apply_hunk_selection(
+    file_index: usize,
+    change: &FileSnapshotChange,
+    rejected: &HashSet<String>,
+) -> Option<String> {
+    let old_content = change.old_content.as_deref().unwrap_or("");
+    let new_content = change.new_content.as_deref().unwrap_or("");
+    let text_diff = TextDiff::from_lines(old_content, new_content);
+
+    let groups = text_diff.grouped_ops(DEFAULT_PATCH_CONTEXT_LINES);
+    let mut owners = HashMap::new();
+    for (hunk_index, group) in groups.iter().enumerate() {
+        for op in group.iter().filter(|op| op.tag() != DiffTag::Equal) {
+            owners.insert((op.old_range().start, op.new_range().start), hunk_index);
+        }
+    }
+    let rejected_hunks = (0..groups.len())
+        .filter(|hunk_index| rejected.contains(&hunk_id(file_index, *hunk_index)))
+        .count();
+
+    let old_lines = text_diff.old_slices();
+    let new_lines = text_diff.new_slices();
+    let mut out = String::new();
+    for op in text_diff.ops() {
+        let keep_old = op.tag() != DiffTag::Equal
+            && owners
+                .get(&(op.old_range().start, op.new_range().start))
+                .is_some_and(|hunk_index| rejected.contains(&hunk_id(file_index, *hunk_index)));
+        if keep_old {
+            out.extend(old_lines[op.old_range()].iter().copied());
+        } else {
+            out.extend(new_lines[op.new_range()].iter().copied());
+        }
+    }
+
+    match change.change_type {
+        FileChangeType::Created if !groups.is_empty() && rejected_hunks == groups.len() => None,
+        FileChangeType::Deleted if rejected_hunks == 0 => None,
+        _ => Some(out),
+    }
+}
+
+pub fn rejected_hunk_ids(diff: &DiffRecord) -> HashSet<String> {
+    diff.hunk_reviews
+        .iter()
+        .filter(|r| r.status == HunkReviewStatus::Rejected)
+        .map(|r| r.hunk_id.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::HunkReview;
+
+    fn lines(range: std::ops::Range<usize>, changed: &[usize]) -> String {
+        range
+            .map(|i| {
+                if changed.contains(&i) {
+                    format!("changed {i}\n")
+                } else {
+                    format!("line {i}\n")
+                }
+            })
+            .collect()
+    }
+
+    fn change(
+        change_type: FileChangeType,
+        old: Option<&str>,
+        new: Option<&str>,
+    ) -> FileSnapshotChange {
+        FileSnapshotChange {
+            file_path: "f.txt".to_string(),
+            change_type,
+            old_content: old.map(str::to_string),
+            new_content: new.map(str::to_string),
+        }
+    }
+
+    fn diff(files: Vec<FileSnapshotChange>, hunk_reviews: Vec<HunkReview>) -> DiffRecord {
+        DiffRecord {
+            id: "d".to_string(),
+            thread_id: "t".to_string(),
+            message_id: None,
+            summary: None,
+            created_at_ms: 0,
+            files,
+            hunk_reviews,
+        }
+    }
+
+    fn rejected(ids: &[&str]) -> HashSet<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn splits_distant_changes_into_hunks() {
+        let old = lines(0..30, &[]);
+        let new = lines(0..30, &[2, 25]);
+        let hunks = diff_hunks(&diff(
+            vec![change(FileChangeType::Modified, Some(&old), Some(&new))],
+            vec![HunkReview {
+                hunk_id: "0:1".to_string(),
+                status: HunkReviewStatus::Rejected,
+                reviewed_at_ms: 0,
+            }],
+        ));
+        assert_eq!(hunks.len(), 2);
+        assert_eq!((hunks[0].id.as_str(), hunks[0].old_start), ("0:0", 1));
+        assert_eq!(hunks[0].status, None);
+        assert_eq!((hunks[1].id.as_str(), hunks[1].old_start), ("0:1", 23));
+        assert_eq!(hunks[1].status, Some(HunkReviewStatus::Rejected));
+        assert!(hunks[1].text.contains("-line 25\n+changed 25\n"));
+    }
+
+    #[test]
+    fn rejected_hunks_keep_old_lines() {
+        let old = lines(0..30, &[]);
+        let new = lines(0..30, &[2, 25]);
+        let file = change(FileChangeType::Modified, Some(&old), Some(&new));
+
+        assert_eq!(
+            apply_hunk_selection(0, &file, &rejected(&[])),
+            Some(new.clone())
+        );
+        assert_eq!(
+            apply_hunk_selection(0, &file, &rejected(&["0:0", "0:1"])),
+            Some(old)
+        );
+        assert_eq!(
+            apply_hunk_selection(0, &file, &rejected(&["0:1"])),
+            Some(lines(0..30, &[2]))
+        );
+        // Ids of another file in the same diff do not apply.
+        assert_eq!(
+            apply_hunk_selection(0, &file, &rejected(&["1:0"])),
+            Some(new)
+        );
+    }
+
+    #[test]
+    fn created_and_deleted_files() {
+        let created = change(FileChangeType::Created, None, Some("a\n"));
+        assert_eq!(apply_hunk_selection(0, &created, &rejected(&["0:0"])), None);
+        assert_eq!(
+            apply_hunk_selection(0, &created, &rejected(&[])),
+            Some("a\n".to_string())
+        );
+
+        let deleted = change(FileChangeType::Deleted, Some("a\n"), None);
+        assert_eq!(apply_hunk_selection(0, &deleted, &rejected(&[])), None);
+        assert_eq!(
+            apply_hunk_selection(0, &deleted, &rejected(&["0:0"])),
+            Some("a\n".to_string())
+        );
+    }
+}
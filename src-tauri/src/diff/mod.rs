@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod hunks;
+pub mod patch;
+pub mod revert;
+pub mod squash;
@@ -0,0 +1,135 @@
+use similar::TextDiff;
+
+use crate::core::models::{FileChangeType, FileSnapshotChange};
+
+const DEV_NULL: &str = "/dev/null";
+const FILE_MODE: &str = "100644";
+
+pub fn render_patch(changes: &[FileSnapshotChange], context_lines: usize) -> String {
+    let mut out = String::new();
+    for change in changes {
+        render_file_patch(&mut out, change, context_lines);
+    }
+    out
+}
+
+fn quote_path(prefix: &str, path: &str) -> String {
+    let name = format!("{prefix}{path}");
+    if name
+        .bytes()
+        .all(|b| b.is_ascii_graphic() && b != b'"' && b != b'\\')
+    {
+        return name;
+    }
+
+    let mut quoted = String::from("\"");
+    for byte in name.bytes() {
+        match byte {
+            b'"' => quoted.push_str("\\\""),
+            b'\\' => quoted.push_str("\\\\"),
+            b'\t' => quoted.push_str("\\t"),
+            b'\n' => quoted.push_str("\\n"),
+            b'\r' => quoted.push_str("\\r"),
+            b' '..=b'~' => quoted.push(byte as char),
+            _ => quoted.push_str(&format!("\\{byte:03o}")),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn render_file_patch(out: &mut String, change: &FileSnapshotChange, context_lines: usize) {
+    let old_name = quote_path("a/", &change.file_path);
+    let new_name = quote_path("b/", &change.file_path);
+    out.push_str(&format!("diff --git {old_name} {new_name}\n"));
+
+    let (old_label, new_label) = match change.change_type {
+        FileChangeType::Created => {
+            out.push_str(&format!("new file mode {FILE_MODE}\n"));
+            (DEV_NULL.to_string(), new_name)
+        }
+        FileChangeType::Deleted => {
+            out.push_str(&format!("deleted file mode {FILE_MODE}\n"));
+            (old_name, DEV_NULL.to_string())
+        }
+        FileChangeType::Modified => (old_name, new_name),
+    };
+
+    let old_content = change.old_content.as_deref().unwrap_or("");
+    let new_content = change.new_content.as_deref().unwrap_or("");
+    let diff = TextDiff::from_lines(old_content, new_content);
+    let body = diff
+        .unified_diff()
+        .context_radius(context_lines)
+        .header(&old_label, &new_label)
+        .to_string();
+    out.push_str(&body);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(
+        path: &str,
+        change_type: FileChangeType,
+        old: Option<&str>,
+        new: Option<&str>,
+    ) -> FileSnapshotChange {
+        FileSnapshotChange {
+            file_path: path.to_string(),
+            change_type,
+            old_content: old.map(str::to_string),
+            new_content: new.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn renders_git_headers_for_each_change_type() {
+        let patch = render_patch(
+            &[
+                change("src/new.rs", FileChangeType::Created, None, Some("a\n")),
+                change("old.txt", FileChangeType::Deleted, Some("b\n"), None),
+                change(
+                    "lib.rs",
+                    FileChangeType::Modified,
+                    Some("x\ny\n"),
+                    Some("x\nz\n"),
+                ),
+            ],
+            3,
+        );
+        assert!(patch.contains(
+            "diff --git a/src/new.rs b/src/new.rs\nnew file mode 100644\n--- /dev/null\n+++ b/src/new.rs\n@@ -0,0 +1 @@\n+a\n"
+        ));
+        assert!(patch.contains(
+            "diff --git a/old.txt b/old.txt\ndeleted file mode 100644\n--- a/old.txt\n+++ /dev/null\n"
+        ));
+        assert!(patch.contains("--- a/lib.rs\n+++ b/lib.rs\n@@ -1,2 +1,2 @@\n x\n-y\n+z\n"));
+    }
+
+    #[test]
+    fn quotes_unusual_paths() {
+        assert_eq!(quote_path("a/", "src/main.rs"), "a/src/main.rs");
+        assert_eq!(quote_path("a/", "my file.txt"), "\"a/my file.txt\"");
+        assert_eq!(
+            quote_path("b/", "say \"hi\"\\x"),
+            "\"b/say \\\"hi\\\"\\\\x\""
+        );
+        assert_eq!(quote_path("a/", "tab\there"), "\"a/tab\\there\"");
+        assert_eq!(quote_path("a/", "caf\u{e9}"), "\"a/caf\\303\\251\"");
+
+        let patch = render_patch(
+            &[change(
+                "my file.txt",
+                FileChangeType::Modified,
+                Some("a\n"),
+                Some("b\n"),
+            )],
+            3,
+        );
+        assert!(patch.starts_with(
+            "diff --git \"a/my file.txt\" \"b/my file.txt\"\n--- \"a/my file.txt\"\n+++ \"b/my file.txt\"\n"
+        ));
+    }
+}
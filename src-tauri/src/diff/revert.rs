@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::agent::paths::resolve_path_in_workspace;
+use crate::core::models::DiffRecord;
+use crate::diff::hunks::{apply_hunk_selection, rejected_hunk_ids};
+
+pub fn write_file_state(path: &Path, content: Option<&str>) -> Result<(), String> {
+    match content {
+        Some(content) => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            fs::write(path, content.as_bytes()).map_err(|e| e.to_string())
+        }
+        None => {
+            if path.exists() {
+                fs::remove_file(path).map_err(|e| e.to_string())?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// `diffs` must be oldest first; they are undone newest first.
+pub fn revert_diffs(workspace_root: &Path, diffs: &[DiffRecord]) -> Result<(), String> {
+    let mut states: HashMap<PathBuf, Option<String>> = HashMap::new();
+    let mut order = Vec::new();
+
+    for diff in diffs.iter().rev() {
+        let rejected = rejected_hunk_ids(diff);
+        for (file_index, change) in diff.files.iter().enumerate() {
+            let resolved = resolve_path_in_workspace(workspace_root, &change.file_path, true)?;
+            if !states.contains_key(&resolved) {
+                let current = if resolved.is_file() {
+                    Some(fs::read_to_string(&resolved).map_err(|e| e.to_string())?)
+                } else {
+                    None
+                };
+                states.insert(resolved.clone(), current);
+                order.push(resolved.clone());
+            }
+
+            let expected = apply_hunk_selection(file_index, change, &rejected);
+            let state = states.get_mut(&resolved).expect("state was just inserted");
+            if *state != expected {
+                return Err(format!(
+                    "File has changed since the diff was recorded: {}",
+                    change.file_path
+                ));
+            }
+            *state = change.old_content.clone();
+        }
+    }
+
+    for path in order {
+        write_file_state(&path, states[&path].as_deref())?;
+    }
+    Ok(())
+}
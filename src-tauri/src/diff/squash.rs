@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+
+use crate::core::models::{DiffRecord, FileChangeType, FileSnapshotChange};
+use crate::diff::hunks::{apply_hunk_selection, rejected_hunk_ids};
+
+pub fn classify_change(
+    old_content: &Option<String>,
+    new_content: &Option<String>,
+) -> FileChangeType {
+    match (old_content, new_content) {
+        (None, Some(_)) => FileChangeType::Created,
+        (Some(_), None) => FileChangeType::Deleted,
+        _ => FileChangeType::Modified,
+    }
+}
+
+pub fn normalize_file_path(workspace_path: &str, file_path: &str) -> String {
+    let path = file_path.replace('\\', "/");
+    let workspace = workspace_path.replace('\\', "/");
+    let workspace = workspace.trim_end_matches('/');
+
+    let relative = match path.strip_prefix(workspace) {
+        Some(rest) if !workspace.is_empty() && rest.starts_with('/') => rest,
+        _ => path.as_str(),
+    };
+    relative
+        .trim_start_matches("./")
+        .trim_start_matches('/')
+        .to_string()
+}
+
+pub fn squash_file_changes(diffs: &[DiffRecord], workspace_path: &str) -> Vec<FileSnapshotChange> {
+    let mut net: BTreeMap<String, (Option<String>, Option<String>)> = BTreeMap::new();
+    for diff in diffs {
+        let rejected = rejected_hunk_ids(diff);
+        for (file_index, file) in diff.files.iter().enumerate() {
+            let key = normalize_file_path(workspace_path, &file.file_path);
+            // Rejected hunks were reverted on disk, so they are not part of the net change.
+            let new_content = if rejected.is_empty() {
+                file.new_content.clone()
+            } else {
+                apply_hunk_selection(file_index, file, &rejected)
+            };
+            net.entry(key)
+                .and_modify(|entry| entry.1 = new_content.clone())
+                .or_insert_with(|| (file.old_content.clone(), new_content));
+        }
+    }
+
+    net.into_iter()
+        .filter(|(_, (old_content, new_content))| old_content != new_content)
+        .map(
+            |(file_path, (old_content, new_content))| FileSnapshotChange {
+                file_path,
+                change_type: classify_change(&old_content, &new_content),
+                old_content,
+                new_content,
+            },
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{HunkReview, HunkReviewStatus};
+    use crate::diff::hunks::hunk_id;
+
+    fn file(path: &str, old: Option<&str>, new: Option<&str>) -> FileSnapshotChange {
+        let old_content = old.map(str::to_string);
+        let new_content = new.map(str::to_string);
+        FileSnapshotChange {
+            file_path: path.to_string(),
+            change_type: classify_change(&old_content, &new_content),
+            old_content,
+            new_content,
+        }
+    }
+
+    fn diff(files: Vec<FileSnapshotChange>) -> DiffRecord {
+        DiffRecord {
+            id: "d".to_string(),
+            thread_id: "t".to_string(),
+            message_id: None,
+            summary: None,
+            created_at_ms: 0,
+            files,
+            hunk_reviews: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn keeps_the_first_old_and_the_last_new_content() {
+        let diffs = vec![
+            diff(vec![file("/ws/a.txt", Some("one\n"), Some("two\n"))]),
+            diff(vec![file("a.txt", Some("two\n"), Some("three\n"))]),
+        ];
+        let changes = squash_file_changes(&diffs, "/ws");
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].file_path, "a.txt");
+        assert!(matches!(changes[0].change_type, FileChangeType::Modified));
+        assert_eq!(changes[0].old_content.as_deref(), Some("one\n"));
+        assert_eq!(changes[0].new_content.as_deref(), Some("three\n"));
+    }
+
+    #[test]
+    fn create_then_delete_is_a_no_op() {
+        let diffs = vec![
+            diff(vec![file("tmp.txt", None, Some("scratch\n"))]),
+            diff(vec![file("tmp.txt", Some("scratch\n"), None)]),
+        ];
+        assert!(squash_file_changes(&diffs, "/ws").is_empty());
+    }
+
+    #[test]
+    fn edits_that_cancel_out_are_dropped() {
+        let diffs = vec![
+            diff(vec![file("a.txt", Some("one\n"), Some("two\n"))]),
+            diff(vec![file("a.txt", Some("two\n"), Some("one\n"))]),
+        ];
+        assert!(squash_file_changes(&diffs, "/ws").is_empty());
+    }
+
+    #[test]
+    fn delete_then_create_is_a_modification() {
+        let diffs = vec![
+            diff(vec![file("a.txt", Some("old\n"), None)]),
+            diff(vec![file("a.txt", None, Some("new\n"))]),
+        ];
+        let changes = squash_file_changes(&diffs, "/ws");
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(changes[0].change_type, FileChangeType::Modified));
+    }
+
+    #[test]
+    fn rejected_hunks_are_left_out() {
+        let mut reviewed = diff(vec![file("a.txt", Some("one\n"), Some("two\n"))]);
+        reviewed.hunk_reviews.push(HunkReview {
+            hunk_id: hunk_id(0, 0),
+            status: HunkReviewStatus::Rejected,
+            reviewed_at_ms: 0,
+        });
+        assert!(squash_file_changes(&[reviewed.clone()], "/ws").is_empty());
+
+        let diffs = vec![
+            reviewed,
+            diff(vec![file("a.txt", Some("one\n"), Some("three\n"))]),
+        ];
+        let changes = squash_file_changes(&diffs, "/ws");
+        assert_eq!(changes[0].old_content.as_deref(), Some("one\n"));
+        assert_eq!(changes[0].new_content.as_deref(), Some("three\n"));
+    }
+}
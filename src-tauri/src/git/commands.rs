@@ -0,0 +1,223 @@
+use std::path::Path;
+
+use crate::agent::paths::resolve_path_in_workspace;
+use crate::core::workspace::canonicalize_workspace;
+use crate::git::runner::{has_head, run_git, validate_branch_name, validate_revision};
+use crate::git::status::parse_porcelain_v2;
+use crate::git::types::{GitBranch, GitCommit, GitStatus};
+
+const EMPTY_TREE_OID: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+const FIELD_SEP: char = '\u{1f}';
+const RECORD_SEP: char = '\u{1e}';
+
+fn workspace_relative_paths(
+    workspace_root: &Path,
+    paths: &[String],
+) -> Result<Vec<String>, String> {
+    let mut out = Vec::with_capacity(paths.len());
+    for path in paths {
+        let resolved = resolve_path_in_workspace(workspace_root, path, true)?;
+        let relative = resolved
+            .strip_prefix(workspace_root)
+            .map_err(|_| "Path is outside workspace".to_string())?;
+        let relative = relative.to_string_lossy().to_string();
+        out.push(if relative.is_empty() {
+            ".".to_string()
+        } else {
+            relative
+        });
+    }
+    Ok(out)
+}
+
+fn read_status(workspace_root: &Path) -> Result<GitStatus, String> {
+    let output = run_git(
+        workspace_root,
+        [
+            "status",
+            "--porcelain=v2",
+            "--branch",
+            "-z",
+            "--untracked-files=all",
+        ],
+    )?;
+    parse_porcelain_v2(&output)
+}
+
+#[tauri::command]
+pub fn git_status(workspace_path: String) -> Result<GitStatus, String> {
+    let workspace_root = canonicalize_workspace(&workspace_path)?;
+    read_status(&workspace_root)
+}
+
+#[tauri::command]
+pub fn git_diff(
+    workspace_path: String,
+    paths: Option<Vec<String>>,
+    staged: Option<bool>,
+) -> Result<String, String> {
+    let workspace_root = canonicalize_workspace(&workspace_path)?;
+    let mut args = vec![
+        "diff".to_string(),
+        "--no-color".to_string(),
+        "--no-ext-diff".to_string(),
+    ];
+    if staged.unwrap_or(false) {
+        args.push("--cached".to_string());
+    } else if has_head(&workspace_root) {
+        args.push("HEAD".to_string());
+    } else {
+        args.push(EMPTY_TREE_OID.to_string());
+    }
+    args.push("--".to_string());
+    args.extend(workspace_relative_paths(
+        &workspace_root,
+        &paths.unwrap_or_default(),
+    )?);
+    run_git(&workspace_root, args)
+}
+
+#[tauri::command]
+pub fn git_stage(workspace_path: String, paths: Vec<String>) -> Result<GitStatus, String> {
+    let workspace_root = canonicalize_workspace(&workspace_path)?;
+    if paths.is_empty() {
+        return Err("No paths to stage".to_string());
+    }
+
+    let mut args = vec!["add".to_string(), "--all".to_string(), "--".to_string()];
+    args.extend(workspace_relative_paths(&workspace_root, &paths)?);
+    run_git(&workspace_root, args)?;
+    read_status(&workspace_root)
+}
+
+#[tauri::command]
+pub fn git_commit(workspace_path: String, message: String) -> Result<GitCommit, String> {
+    let workspace_root = canonicalize_workspace(&workspace_path)?;
+    if message.trim().is_empty() {
+        return Err("Commit message cannot be empty".to_string());
+    }
+
+    run_git(
+        &workspace_root,
+        ["commit", "--quiet", "-m", message.as_str()],
+    )?;
+    read_log(&workspace_root, 1, None)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| "Could not read the new commit".to_string())
+}
+
+#[tauri::command]
+pub fn git_list_branches(workspace_path: String) -> Result<Vec<GitBranch>, String> {
+    let workspace_root = canonicalize_workspace(&workspace_path)?;
+    let format = format!("--format=%(HEAD){FIELD_SEP}%(refname:short){FIELD_SEP}%(upstream:short)");
+    let output = run_git(&workspace_root, ["branch", "--list", format.as_str()])?;
+
+    let branches = output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(FIELD_SEP);
+            let head = fields.next()?;
+            let name = fields.next()?;
+            let upstream = fields.next().filter(|u| !u.is_empty());
+            Some(GitBranch {
+                name: name.to_string(),
+                current: head == "*",
+                upstream: upstream.map(|u| u.to_string()),
+            })
+        })
+        .collect();
+    Ok(branches)
+}
+
+#[tauri::command]
+pub fn git_create_branch(
+    workspace_path: String,
+    name: String,
+    start_point: Option<String>,
+    checkout: Option<bool>,
+) -> Result<GitStatus, String> {
+    let workspace_root = canonicalize_workspace(&workspace_path)?;
+    validate_branch_name(&workspace_root, &name)?;
+
+    if let Some(start_point) = &start_point {
+        validate_revision(&workspace_root, start_point)?;
+    }
+
+    let mut args = if checkout.unwrap_or(true) {
+        vec!["switch".to_string(), "--create".to_string(), name]
+    } else {
+        vec!["branch".to_string(), name]
+    };
+    if let Some(start_point) = start_point {
+        args.push("--end-of-options".to_string());
+        args.push(start_point);
+    }
+    run_git(&workspace_root, args)?;
+    read_status(&workspace_root)
+}
+
+#[tauri::command]
+pub fn git_switch_branch(workspace_path: String, name: String) -> Result<GitStatus, String> {
+    let workspace_root = canonicalize_workspace(&workspace_path)?;
+    validate_branch_name(&workspace_root, &name)?;
+    run_git(&workspace_root, ["switch", "--quiet", name.as_str()])?;
+    read_status(&workspace_root)
+}
+
+#[tauri::command]
+pub fn git_log(
+    workspace_path: String,
+    limit: Option<usize>,
+    revision: Option<String>,
+) -> Result<Vec<GitCommit>, String> {
+    let workspace_root = canonicalize_workspace(&workspace_path)?;
+    match &revision {
+        Some(revision) => validate_revision(&workspace_root, revision)?,
+        None if !has_head(&workspace_root) => return Ok(Vec::new()),
+        None => {}
+    }
+    read_log(
+        &workspace_root,
+        limit.unwrap_or(50).clamp(1, 1000),
+        revision,
+    )
+}
+
+fn read_log(
+    workspace_root: &Path,
+    limit: usize,
+    revision: Option<String>,
+) -> Result<Vec<GitCommit>, String> {
+    let format = format!(
+        "--format=%H{FIELD_SEP}%h{FIELD_SEP}%an{FIELD_SEP}%ae{FIELD_SEP}%at{FIELD_SEP}%s{RECORD_SEP}"
+    );
+    let mut args = vec!["log".to_string(), format!("--max-count={limit}"), format];
+    if let Some(revision) = revision {
+        args.push("--end-of-options".to_string());
+        args.push(revision);
+    }
+    args.push("--".to_string());
+    let output = run_git(workspace_root, args)?;
+
+    let commits = output
+        .split(RECORD_SEP)
+        .map(|record| record.trim_start_matches('\n'))
+        .filter(|record| !record.is_empty())
+        .filter_map(|record| {
+            let fields = record.split(FIELD_SEP).collect::<Vec<_>>();
+            if fields.len() != 6 {
+                return None;
+            }
+            Some(GitCommit {
+                oid: fields[0].to_string(),
+                short_oid: fields[1].to_string(),
+                author_name: fields[2].to_string(),
+                author_email: fields[3].to_string(),
+                authored_at_ms: fields[4].parse::<u64>().unwrap_or(0) * 1000,
+                subject: fields[5].to_string(),
+            })
+        })
+        .collect();
+    Ok(commits)
+}
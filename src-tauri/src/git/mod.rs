@@ -0,0 +1,6 @@
+pub mod commands;
+pub mod runner;
+pub mod status;
+pub mod types;
+pub mod worktree;
+pub mod worktree_commands;
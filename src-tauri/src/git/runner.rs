@@ -0,0 +1,57 @@
+use std::ffi::OsStr;
+use std::path::Path;
+use std::process::Command;
+
+pub fn run_git<I, S>(workdir: &Path, args: I) -> Result<String, String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(workdir)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("LC_ALL", "C")
+        .output()
+        .map_err(|e| format!("Failed to run git: {e}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        if stderr.is_empty() {
+            return Err(format!("git exited with {}", output.status));
+        }
+        return Err(stderr);
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+pub fn has_head(workdir: &Path) -> bool {
+    run_git(workdir, ["rev-parse", "--verify", "--quiet", "HEAD"]).is_ok()
+}
+
+pub fn validate_branch_name(workdir: &Path, name: &str) -> Result<(), String> {
+    run_git(workdir, ["check-ref-format", "--branch", name])
+        .map(|_| ())
+        .map_err(|_| format!("Invalid branch name: {name}"))
+}
+
+pub fn validate_revision(workdir: &Path, revision: &str) -> Result<(), String> {
+    if revision.is_empty() || revision.starts_with('-') {
+        return Err(format!("Invalid revision: {revision}"));
+    }
+    let (first, _) = revision.split_once("..").unwrap_or((revision, ""));
+    let first = if first.is_empty() { "HEAD" } else { first };
+    run_git(
+        workdir,
+        [
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            "--end-of-options",
+            &format!("{first}^{{commit}}"),
+        ],
+    )
+    .map(|_| ())
+    .map_err(|_| format!("Unknown revision: {revision}"))
+}
@@ -0,0 +1,165 @@
+use crate::git::types::{GitFileStatus, GitStatus, GitStatusEntry};
+
+fn parse_status_code(code: char) -> Option<GitFileStatus> {
+    match code {
+        'M' => Some(GitFileStatus::Modified),
+        'T' => Some(GitFileStatus::TypeChanged),
+        'A' => Some(GitFileStatus::Added),
+        'D' => Some(GitFileStatus::Deleted),
+        'R' => Some(GitFileStatus::Renamed),
+        'C' => Some(GitFileStatus::Copied),
+        'U' => Some(GitFileStatus::Unmerged),
+        _ => None,
+    }
+}
+
+fn parse_xy(xy: &str) -> (Option<GitFileStatus>, Option<GitFileStatus>) {
+    let mut chars = xy.chars();
+    let staged = chars.next().and_then(parse_status_code);
+    let unstaged = chars.next().and_then(parse_status_code);
+    (staged, unstaged)
+}
+
+pub fn parse_porcelain_v2(output: &str) -> Result<GitStatus, String> {
+    let mut status = GitStatus::default();
+    let mut records = output.split('\0').filter(|r| !r.is_empty());
+
+    while let Some(record) = records.next() {
+        if let Some(header) = record.strip_prefix("# ") {
+            let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+            match key {
+                "branch.oid" if value != "(initial)" => status.head = Some(value.to_string()),
+                "branch.head" if value != "(detached)" => status.branch = Some(value.to_string()),
+                "branch.upstream" => status.upstream = Some(value.to_string()),
+                "branch.ab" => {
+                    for part in value.split_whitespace() {
+                        if let Some(ahead) = part.strip_prefix('+') {
+                            status.ahead = ahead.parse().unwrap_or(0);
+                        } else if let Some(behind) = part.strip_prefix('-') {
+                            status.behind = behind.parse().unwrap_or(0);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let (kind, rest) = record
+            .split_once(' ')
+            .ok_or_else(|| format!("Unexpected git status record: {record}"))?;
+        let entry = match kind {
+            "1" | "u" => {
+                let field_count = if kind == "1" { 8 } else { 10 };
+                let fields = rest.splitn(field_count, ' ').collect::<Vec<_>>();
+                if fields.len() != field_count {
+                    return Err(format!("Unexpected git status record: {record}"));
+                }
+                let (staged, unstaged) = parse_xy(fields[0]);
+                GitStatusEntry {
+                    path: fields[field_count - 1].to_string(),
+                    orig_path: None,
+                    staged,
+                    unstaged,
+                }
+            }
+            "2" => {
+                let fields = rest.splitn(9, ' ').collect::<Vec<_>>();
+                if fields.len() != 9 {
+                    return Err(format!("Unexpected git status record: {record}"));
+                }
+                let (staged, unstaged) = parse_xy(fields[0]);
+                GitStatusEntry {
+                    path: fields[8].to_string(),
+                    orig_path: records.next().map(|p| p.to_string()),
+                    staged,
+                    unstaged,
+                }
+            }
+            "?" => GitStatusEntry {
+                path: rest.to_string(),
+                orig_path: None,
+                staged: None,
+                unstaged: Some(GitFileStatus::Untracked),
+            },
+            _ => continue,
+        };
+        status.entries.push(entry);
+    }
+
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_branch_headers_and_entries() {
+        let output = [
+            "# branch.oid c917caa0",
+            "# branch.head main",
+            "# branch.upstream origin/main",
+            "# branch.ab +2 -1",
+            "1 MM N... 100644 100644 100644 78981922 c1827f07 a.txt",
+            "1 .D N... 100644 100644 000000 61780798 61780798 b.txt",
+            "2 R. N... 100644 100644 100644 f2ad6c76 f2ad6c76 R100 new name.txt",
+            "old name.txt",
+            "? un tracked.txt",
+            "",
+        ]
+        .join("\0");
+        let status = parse_porcelain_v2(&output).unwrap();
+
+        assert_eq!(status.head.as_deref(), Some("c917caa0"));
+        assert_eq!(status.branch.as_deref(), Some("main"));
+        assert_eq!(status.upstream.as_deref(), Some("origin/main"));
+        assert_eq!((status.ahead, status.behind), (2, 1));
+
+        let summary = status
+            .entries
+            .iter()
+            .map(|e| {
+                (
+                    e.path.as_str(),
+                    e.orig_path.as_deref(),
+                    e.staged.clone(),
+                    e.unstaged.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                (
+                    "a.txt",
+                    None,
+                    Some(GitFileStatus::Modified),
+                    Some(GitFileStatus::Modified)
+                ),
+                ("b.txt", None, None, Some(GitFileStatus::Deleted)),
+                (
+                    "new name.txt",
+                    Some("old name.txt"),
+                    Some(GitFileStatus::Renamed),
+                    None
+                ),
+                ("un tracked.txt", None, None, Some(GitFileStatus::Untracked)),
+            ]
+        );
+    }
+
+    #[test]
+    fn initial_and_detached_heads_are_unset() {
+        let status =
+            parse_porcelain_v2("# branch.oid (initial)\0# branch.head (detached)\0").unwrap();
+        assert_eq!(status.head, None);
+        assert_eq!(status.branch, None);
+        assert!(status.entries.is_empty());
+    }
+
+    #[test]
+    fn rejects_truncated_records() {
+        assert!(parse_porcelain_v2("1 M. N... 100644\0").is_err());
+    }
+}
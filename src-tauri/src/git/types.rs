@@ -0,0 +1,49 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GitFileStatus {
+    Modified,
+    TypeChanged,
+    Added,
+    Deleted,
+    Renamed,
+    Copied,
+    Unmerged,
+    Untracked,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GitStatusEntry {
+    pub path: String,
+    pub orig_path: Option<String>,
+    pub staged: Option<GitFileStatus>,
+    pub unstaged: Option<GitFileStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GitStatus {
+    pub branch: Option<String>,
+    pub head: Option<String>,
+    pub upstream: Option<String>,
+    pub ahead: u32,
+    pub behind: u32,
+    pub entries: Vec<GitStatusEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GitCommit {
+    pub oid: String,
+    pub short_oid: String,
+    pub author_name: String,
+    pub author_email: String,
+    pub authored_at_ms: u64,
+    pub subject: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GitBranch {
+    pub name: String,
+    pub current: bool,
+    pub upstream: Option<String>,
+}
@@ -0,0 +1,256 @@
+use std::fs;
+use std::path::Path;
+
+use crate::core::constants::WORKTREE_BRANCH_PREFIX;
+use crate::core::models::{ProjectRecord, ThreadRecord, ThreadWorktree};
+use crate::core::time::now_ms;
+use crate::core::workspace::canonicalize_workspace;
+use crate::git::runner::{has_head, run_git, validate_branch_name};
+use crate::storage::paths::thread_worktree_dir;
+
+pub fn thread_workspace_path(project: &ProjectRecord, thread: &ThreadRecord) -> String {
+    thread
+        .worktree
+        .as_ref()
+        .map(|w| w.path.clone())
+        .unwrap_or_else(|| project.workspace_path.clone())
+}
+
+pub fn create_worktree(
+    root: &Path,
+    project: &ProjectRecord,
+    thread_id: &str,
+    branch: Option<String>,
+) -> Result<ThreadWorktree, String> {
+    let workspace_root = canonicalize_workspace(&project.workspace_path)?;
+    if !has_head(&workspace_root) {
+        return Err("Workspace has no commits to branch from".to_string());
+    }
+
+    let branch = branch.unwrap_or_else(|| format!("{WORKTREE_BRANCH_PREFIX}{thread_id}"));
+    validate_branch_name(&workspace_root, &branch)?;
+
+    let path = thread_worktree_dir(root, thread_id);
+    if path.exists() {
+        return Err(format!(
+            "Worktree directory already exists: {}",
+            path.display()
+        ));
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let base_commit = run_git(&workspace_root, ["rev-parse", "HEAD"])?
+        .trim()
+        .to_string();
+    let base_branch = run_git(
+        &workspace_root,
+        ["symbolic-ref", "--quiet", "--short", "HEAD"],
+    )
+    .ok()
+    .map(|b| b.trim().to_string());
+
+    let path_arg = path.to_string_lossy().to_string();
+    run_git(
+        &workspace_root,
+        [
+            "worktree",
+            "add",
+            "--quiet",
+            "-b",
+            &branch,
+            &path_arg,
+            &base_commit,
+        ],
+    )?;
+
+    Ok(ThreadWorktree {
+        path: path_arg,
+        branch,
+        base_branch,
+        base_commit,
+        created_at_ms: now_ms(),
+    })
+}
+
+pub fn merge_worktree(
+    project: &ProjectRecord,
+    worktree: &ThreadWorktree,
+    message: Option<String>,
+) -> Result<(), String> {
+    let workspace_root = canonicalize_workspace(&project.workspace_path)?;
+    let base_branch = worktree
+        .base_branch
+        .as_deref()
+        .ok_or("Worktree was created from a detached HEAD and has no branch to merge into")?;
+    let current = run_git(
+        &workspace_root,
+        ["symbolic-ref", "--quiet", "--short", "HEAD"],
+    )
+    .unwrap_or_default();
+    if current.trim() != base_branch {
+        return Err(format!(
+            "Workspace must be on {base_branch} to merge {}",
+            worktree.branch
+        ));
+    }
+    if !run_git(&workspace_root, ["status", "--porcelain"])?
+        .trim()
+        .is_empty()
+    {
+        return Err("Workspace has uncommitted changes".to_string());
+    }
+
+    let worktree_root = Path::new(&worktree.path);
+    let pending = run_git(worktree_root, ["status", "--porcelain"])?;
+    if !pending.trim().is_empty() {
+        let message = message.unwrap_or_else(|| format!("Changes from {}", worktree.branch));
+        run_git(worktree_root, ["add", "--all"])?;
+        run_git(worktree_root, ["commit", "--quiet", "-m", &message])?;
+    }
+
+    let merge_message = format!("Merge {}", worktree.branch);
+    if let Err(err) = run_git(
+        &workspace_root,
+        [
+            "merge",
+            "--no-ff",
+            "--quiet",
+            "-m",
+            &merge_message,
+            &worktree.branch,
+        ],
+    ) {
+        let _ = run_git(&workspace_root, ["merge", "--abort"]);
+        return Err(format!("Merge failed: {err}"));
+    }
+    Ok(())
+}
+
+pub fn remove_worktree(
+    project: &ProjectRecord,
+    worktree: &ThreadWorktree,
+    force_delete_branch: bool,
+) -> Result<(), String> {
+    let workspace_root = canonicalize_workspace(&project.workspace_path)?;
+    if Path::new(&worktree.path).exists() {
+        run_git(
+            &workspace_root,
+            ["worktree", "remove", "--force", worktree.path.as_str()],
+        )?;
+    }
+    run_git(&workspace_root, ["worktree", "prune"])?;
+
+    let delete_flag = if force_delete_branch { "-D" } else { "-d" };
+    run_git(
+        &workspace_root,
+        ["branch", delete_flag, worktree.branch.as_str()],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::ProjectSettings;
+
+    fn setup(name: &str) -> (std::path::PathBuf, ProjectRecord) {
+        let dir = std::env::temp_dir().join(format!("kodit-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let workspace = dir.join("workspace");
+        fs::create_dir_all(&workspace).unwrap();
+        run_git(&workspace, ["init", "--quiet", "-b", "main"]).unwrap();
+        run_git(&workspace, ["config", "user.name", "Test"]).unwrap();
+        run_git(&workspace, ["config", "user.email", "test@example.com"]).unwrap();
+        fs::write(workspace.join("a.txt"), "one\n").unwrap();
+        run_git(&workspace, ["add", "--all"]).unwrap();
+        run_git(&workspace, ["commit", "--quiet", "-m", "init"]).unwrap();
+
+        let project = ProjectRecord {
+            id: "p".to_string(),
+            name: "p".to_string(),
+            workspace_path: workspace.to_string_lossy().to_string(),
+            created_at_ms: 0,
+            updated_at_ms: 0,
+            archived: false,
+            deleted_at_ms: None,
+            settings: ProjectSettings::default(),
+        };
+        (dir, project)
+    }
+
+    fn branch_exists(project: &ProjectRecord, branch: &str) -> bool {
+        let workspace = Path::new(&project.workspace_path);
+        run_git(workspace, ["rev-parse", "--verify", "--quiet", branch]).is_ok()
+    }
+
+    #[test]
+    fn merges_worktree_changes_into_the_base_branch() {
+        let (dir, project) = setup("worktree-merge");
+        let worktree = create_worktree(&dir.join("storage"), &project, "t1", None).unwrap();
+        assert_eq!(worktree.base_branch.as_deref(), Some("main"));
+        assert!(branch_exists(&project, &worktree.branch));
+
+        fs::write(Path::new(&worktree.path).join("a.txt"), "two\n").unwrap();
+        merge_worktree(&project, &worktree, None).unwrap();
+        remove_worktree(&project, &worktree, false).unwrap();
+
+        let workspace = Path::new(&project.workspace_path);
+        assert_eq!(
+            fs::read_to_string(workspace.join("a.txt")).unwrap(),
+            "two\n"
+        );
+        assert!(!Path::new(&worktree.path).exists());
+        assert!(!branch_exists(&project, &worktree.branch));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn refuses_to_merge_into_a_dirty_or_switched_workspace() {
+        let (dir, project) = setup("worktree-refuse");
+        let worktree = create_worktree(&dir.join("storage"), &project, "t1", None).unwrap();
+        fs::write(Path::new(&worktree.path).join("a.txt"), "two\n").unwrap();
+        let workspace = Path::new(&project.workspace_path);
+
+        fs::write(workspace.join("local.txt"), "wip\n").unwrap();
+        assert!(merge_worktree(&project, &worktree, None)
+            .unwrap_err()
+            .contains("uncommitted"));
+        fs::remove_file(workspace.join("local.txt")).unwrap();
+
+        run_git(workspace, ["checkout", "--quiet", "-b", "other"]).unwrap();
+        assert!(merge_worktree(&project, &worktree, None)
+            .unwrap_err()
+            .contains("main"));
+        assert_eq!(
+            fs::read_to_string(workspace.join("a.txt")).unwrap(),
+            "one\n"
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn discarding_removes_the_worktree_and_its_branch() {
+        let (dir, project) = setup("worktree-discard");
+        let worktree = create_worktree(&dir.join("storage"), &project, "t1", None).unwrap();
+        fs::write(Path::new(&worktree.path).join("a.txt"), "two\n").unwrap();
+        run_git(
+            Path::new(&worktree.path),
+            ["commit", "--quiet", "-am", "wip"],
+        )
+        .unwrap();
+
+        remove_worktree(&project, &worktree, true).unwrap();
+
+        let workspace = Path::new(&project.workspace_path);
+        assert_eq!(
+            fs::read_to_string(workspace.join("a.txt")).unwrap(),
+            "one\n"
+        );
+        assert!(!Path::new(&worktree.path).exists());
+        assert!(!branch_exists(&project, &worktree.branch));
+        assert!(create_worktree(&dir.join("storage"), &project, "t1", None).is_ok());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,93 @@
+use rusqlite::TransactionBehavior;
+use tauri::AppHandle;
+
+use crate::core::models::ThreadRecord;
+use crate::core::time::now_ms;
+use crate::git::worktree::{create_worktree, merge_worktree, remove_worktree};
+use crate::storage::db::{open_db, put_thread, require_project, require_thread};
+use crate::storage::io::ensure_storage_ready;
+use crate::storage::lock::lock_thread;
+use crate::storage::paths::storage_root;
+
+#[tauri::command]
+pub fn create_thread_worktree(
+    app: AppHandle,
+    thread_id: String,
+    branch: Option<String>,
+) -> Result<ThreadRecord, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let _thread_lock = lock_thread(&root, &thread_id)?;
+    let mut conn = open_db(&root)?;
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let mut thread = require_thread(&tx, &thread_id)?;
+    if thread.worktree.is_some() {
+        return Err(format!("Thread already has a worktree: {thread_id}"));
+    }
+    let project = require_project(&tx, &thread.project_id)?;
+
+    let worktree = create_worktree(&root, &project, &thread.id, branch)?;
+    thread.worktree = Some(worktree.clone());
+    thread.updated_at_ms = now_ms();
+    let saved = put_thread(&tx, &thread).and_then(|()| tx.commit().map_err(|e| e.to_string()));
+    if let Err(error) = saved {
+        let _ = remove_worktree(&project, &worktree, true);
+        return Err(error);
+    }
+    Ok(thread)
+}
+
+#[tauri::command]
+pub fn merge_thread_worktree(
+    app: AppHandle,
+    thread_id: String,
+    message: Option<String>,
+) -> Result<ThreadRecord, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let _thread_lock = lock_thread(&root, &thread_id)?;
+    let mut conn = open_db(&root)?;
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let mut thread = require_thread(&tx, &thread_id)?;
+    let project = require_project(&tx, &thread.project_id)?;
+    let worktree = thread
+        .worktree
+        .take()
+        .ok_or_else(|| format!("Thread has no worktree: {thread_id}"))?;
+
+    merge_worktree(&project, &worktree, message)?;
+    remove_worktree(&project, &worktree, false)?;
+
+    thread.updated_at_ms = now_ms();
+    put_thread(&tx, &thread)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(thread)
+}
+
+#[tauri::command]
+pub fn discard_thread_worktree(app: AppHandle, thread_id: String) -> Result<ThreadRecord, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let _thread_lock = lock_thread(&root, &thread_id)?;
+    let mut conn = open_db(&root)?;
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let mut thread = require_thread(&tx, &thread_id)?;
+    let project = require_project(&tx, &thread.project_id)?;
+    let worktree = thread
+        .worktree
+        .take()
+        .ok_or_else(|| format!("Thread has no worktree: {thread_id}"))?;
+
+    remove_worktree(&project, &worktree, true)?;
+
+    thread.updated_at_ms = now_ms();
+    put_thread(&tx, &thread)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(thread)
+}
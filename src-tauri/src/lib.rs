@@ -2,6 +2,8 @@ mod agent;
 mod app;
 mod auth;
 mod core;
+mod diff;
+mod git;
 mod storage;
 mod ui;
 
@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use uuid::Uuid;
+
+use crate::core::constants::{THREAD_ARCHIVE_FORMAT, THREAD_ARCHIVE_VERSION};
+use crate::core::models::{
+    DiffRecord, MessagePart, MessageRecord, MessageRole, ThreadArchive, ThreadRecord, TodoEvent,
+    TodoStatus,
+};
+use crate::storage::tree::MessageTree;
+
+pub fn check_archive(archive: &ThreadArchive) -> Result<(), String> {
+    if archive.format != THREAD_ARCHIVE_FORMAT {
+        return Err("File is not a Kodit thread archive".to_string());
+    }
+    if archive.version > THREAD_ARCHIVE_VERSION {
+        return Err(format!(
+            "Thread archive version {} is newer than this build supports ({THREAD_ARCHIVE_VERSION}); update Kodit to import it",
+            archive.version
+        ));
+    }
+    Ok(())
+}
+
+pub fn remap_archive(archive: ThreadArchive, project_id: &str, ts: u64) -> ThreadArchive {
+    let thread_id = Uuid::new_v4().to_string();
+    let mut messages = archive.messages;
+    messages.sort_by_key(|m| m.sequence);
+    let ids = messages
+        .iter()
+        .map(|m| (m.id.clone(), Uuid::new_v4().to_string()))
+        .collect::<HashMap<_, _>>();
+
+    let messages = messages
+        .into_iter()
+        .enumerate()
+        .map(|(index, message)| MessageRecord {
+            id: ids[&message.id].clone(),
+            thread_id: thread_id.clone(),
+            parent_id: message.parent_id.and_then(|p| ids.get(&p).cloned()),
+            sequence: index as u64 + 1,
+            ..message
+        })
+        .collect();
+
+    let diffs = archive
+        .diffs
+        .into_iter()
+        .map(|diff| DiffRecord {
+            id: Uuid::new_v4().to_string(),
+            thread_id: thread_id.clone(),
+            message_id: diff.message_id.and_then(|m| ids.get(&m).cloned()),
+            ..diff
+        })
+        .collect();
+
+    let thread = ThreadRecord {
+        id: thread_id,
+        project_id: project_id.to_string(),
+        updated_at_ms: ts,
+        worktree: None,
+        archived: false,
+        deleted_at_ms: None,
+        active_leaf_id: archive
+            .thread
+            .active_leaf_id
+            .and_then(|id| ids.get(&id).cloned()),
+        forked_from: None,
+        ..archive.thread
+    };
+
+    ThreadArchive {
+        thread,
+        messages,
+        diffs,
+        ..archive
+    }
+}
+
+fn role_heading(message: &MessageRecord) -> String {
+    let role = match message.role {
+        MessageRole::User => "User",
+        MessageRole::Agent => "Agent",
+        MessageRole::System => "System",
+    };
+    match &message.model {
+        Some(model) => format!("{role} ({model})"),
+        None => role.to_string(),
+    }
+}
+
+pub fn render_markdown(
+    thread: &ThreadRecord,
+    messages: &[MessageRecord],
+    diffs: &[DiffRecord],
+) -> String {
+    let tree = MessageTree::new(messages);
+    let branch = tree
+        .active_leaf(thread.active_leaf_id.as_deref())
+        .map(|leaf| tree.path_to(leaf))
+        .unwrap_or_default();
+
+    let mut out = format!("# {}\n", thread.title);
+    if !thread.todos.is_empty() {
+        out.push_str("\n## Todos\n\n");
+        for todo in &thread.todos {
+            let (mark, suffix) = match todo.status {
+                TodoStatus::Completed => ("x", ""),
+                TodoStatus::InProgress => (" ", " (in progress)"),
+                TodoStatus::Cancelled => (" ", " (cancelled)"),
+                TodoStatus::Pending => (" ", ""),
+            };
+            let _ = writeln!(out, "- [{mark}] {}{suffix}", todo.content);
+        }
+    }
+    for message in branch {
+        let _ = write!(out, "\n## {}\n\n", role_heading(message));
+        for part in &message.parts {
+            match part {
+                MessagePart::Text { text } => {
+                    let _ = writeln!(out, "{}\n", text.trim_end());
+                }
+                MessagePart::Reasoning { text } => {
+                    let _ = writeln!(
+                        out,
+                        "<details>\n<summary>Reasoning</summary>\n\n{}\n\n</details>\n",
+                        text.trim_end()
+                    );
+                }
+                MessagePart::ToolCall { name, .. } => {
+                    let _ = writeln!(out, "- Tool: `{name}`");
+                }
+                MessagePart::ToolResult { .. } => {}
+                MessagePart::Attachment {
+                    name, mime_type, ..
+                } => {
+                    let _ = writeln!(out, "- Attachment: {name} ({mime_type})");
+                }
+            }
+        }
+
+        let files = diffs
+            .iter()
+            .filter(|d| d.message_id.as_deref() == Some(message.id.as_str()))
+            .flat_map(|d| &d.files)
+            .map(|f| f.file_path.as_str())
+            .collect::<Vec<_>>();
+        if !files.is_empty() {
+            out.push_str("\nChanged files:\n\n");
+            for file in files {
+                let _ = writeln!(out, "- `{file}`");
+            }
+        }
+    }
+    out
+}
+
+pub fn new_archive(
+    thread: ThreadRecord,
+    messages: Vec<MessageRecord>,
+    diffs: Vec<DiffRecord>,
+    todo_history: Vec<TodoEvent>,
+    ts: u64,
+) -> ThreadArchive {
+    ThreadArchive {
+        format: THREAD_ARCHIVE_FORMAT.to_string(),
+        version: THREAD_ARCHIVE_VERSION,
+        exported_at_ms: ts,
+        thread,
+        messages,
+        diffs,
+        todo_history,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{AgentMode, FileChangeType, FileSnapshotChange, TodoItem};
+
+    fn message(
+        id: &str,
+        parent: Option<&str>,
+        sequence: u64,
+        parts: Vec<MessagePart>,
+    ) -> MessageRecord {
+        MessageRecord {
+            id: id.to_string(),
+            thread_id: "t".to_string(),
+            role: MessageRole::User,
+            content: String::new(),
+            model: None,
+            provider: None,
+            mode: AgentMode::Build,
+            tokens: Default::default(),
+            parent_id: parent.map(str::to_string),
+            created_at_ms: sequence,
+            updated_at_ms: sequence,
+            sequence,
+            parts,
+        }
+    }
+
+    fn text(text: &str) -> Vec<MessagePart> {
+        vec![MessagePart::Text {
+            text: text.to_string(),
+        }]
+    }
+
+    fn thread(active_leaf_id: Option<&str>) -> ThreadRecord {
+        ThreadRecord {
+            id: "t".to_string(),
+            project_id: "p".to_string(),
+            title: "Thread".to_string(),
+            mode: AgentMode::Build,
+            created_at_ms: 1,
+            updated_at_ms: 1,
+            worktree: None,
+            archived: true,
+            deleted_at_ms: Some(5),
+            active_leaf_id: active_leaf_id.map(str::to_string),
+            forked_from: None,
+            todos: Vec::new(),
+        }
+    }
+
+    fn diff(message_id: &str, file_path: &str) -> DiffRecord {
+        DiffRecord {
+            id: format!("d-{message_id}"),
+            thread_id: "t".to_string(),
+            message_id: Some(message_id.to_string()),
+            summary: None,
+            created_at_ms: 0,
+            files: vec![FileSnapshotChange {
+                file_path: file_path.to_string(),
+                change_type: FileChangeType::Modified,
+                old_content: Some(String::new()),
+                new_content: Some("x\n".to_string()),
+            }],
+            hunk_reviews: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn remap_gives_the_archive_fresh_ids() {
+        let messages = vec![
+            message("b", Some("a"), 2, Vec::new()),
+            message("a", None, 1, Vec::new()),
+            message("c", Some("gone"), 3, Vec::new()),
+        ];
+        let archive = new_archive(
+            thread(Some("b")),
+            messages,
+            vec![diff("b", "f")],
+            Vec::new(),
+            0,
+        );
+
+        let remapped = remap_archive(archive, "q", 9);
+
+        let thread = &remapped.thread;
+        assert_ne!(thread.id, "t");
+        assert_eq!(thread.project_id, "q");
+        assert_eq!(thread.updated_at_ms, 9);
+        assert!(!thread.archived);
+        assert_eq!(thread.deleted_at_ms, None);
+
+        let [a, b, c] = remapped.messages.as_slice() else {
+            panic!("expected three messages");
+        };
+        assert!(remapped.messages.iter().all(|m| m.thread_id == thread.id));
+        assert_eq!([a.sequence, b.sequence, c.sequence], [1, 2, 3]);
+        assert!(![a, b, c]
+            .iter()
+            .any(|m| ["a", "b", "c"].contains(&m.id.as_str())));
+        assert_eq!(b.parent_id.as_ref(), Some(&a.id));
+        assert_eq!(c.parent_id, None);
+        assert_eq!(thread.active_leaf_id.as_ref(), Some(&b.id));
+
+        assert_eq!(remapped.diffs[0].thread_id, thread.id);
+        assert_eq!(remapped.diffs[0].message_id.as_ref(), Some(&b.id));
+        assert_ne!(remapped.diffs[0].id, "d-b");
+    }
+
+    #[test]
+    fn markdown_follows_the_active_branch() {
+        let mut thread = thread(Some("c"));
+        thread.todos = vec![
+            TodoItem {
+                id: "1".to_string(),
+                content: "Write tests".to_string(),
+                status: TodoStatus::Completed,
+                priority: None,
+            },
+            TodoItem {
+                id: "2".to_string(),
+                content: "Ship".to_string(),
+                status: TodoStatus::InProgress,
+                priority: None,
+            },
+        ];
+        let mut answer = message(
+            "c",
+            Some("a"),
+            3,
+            vec![
+                MessagePart::Reasoning {
+                    text: "thinking\n".to_string(),
+                },
+                MessagePart::Text {
+                    text: "Done.\n".to_string(),
+                },
+            ],
+        );
+        answer.role = MessageRole::Agent;
+        answer.model = Some("m1".to_string());
+        let messages = vec![
+            message("a", None, 1, text("Hello")),
+            message("b", Some("a"), 2, text("Abandoned branch")),
+            answer,
+        ];
+
+        let markdown = render_markdown(
+            &thread,
+            &messages,
+            &[diff("c", "src/lib.rs"), diff("b", "old.rs")],
+        );
+
+        assert_eq!(
+            markdown,
+            "# Thread\n\
+             \n## Todos\n\n\
+             - [x] Write tests\n\
+             - [ ] Ship (in progress)\n\
+             \n## User\n\nHello\n\n\
+             \n## Agent (m1)\n\n\
+             <details>\n<summary>Reasoning</summary>\n\nthinking\n\n</details>\n\n\
+             Done.\n\n\
+             \nChanged files:\n\n\
+             - `src/lib.rs`\n"
+        );
+    }
+}
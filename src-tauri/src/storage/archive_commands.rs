@@ -0,0 +1,97 @@
+use std::fs;
+use std::path::Path;
+
+use rusqlite::TransactionBehavior;
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use crate::core::models::{ThreadArchive, ThreadRecord};
+use crate::core::time::now_ms;
+use crate::storage::archive::{check_archive, new_archive, remap_archive, render_markdown};
+use crate::storage::db::{list_messages_by_sequence, open_db, require_project, require_thread};
+use crate::storage::io::{ensure_storage_ready, insert_thread, read_json_file, read_thread_diffs};
+use crate::storage::paths::{storage_root, thread_diffs_dir};
+use crate::storage::todos::{append_todo_events, list_todo_events};
+
+// A unique sibling name never clobbers a temp file that is not ours.
+fn write_export(path: &Path, contents: &str) -> Result<(), String> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| format!("Not a file path: {}", path.display()))?
+        .to_string_lossy();
+    let tmp = path.with_file_name(format!(".{name}.{}.tmp", Uuid::new_v4()));
+    fs::write(&tmp, contents).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        e.to_string()
+    })
+}
+
+#[tauri::command]
+pub fn export_thread(app: AppHandle, thread_id: String, path: String) -> Result<(), String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let conn = open_db(&root)?;
+    let thread = require_thread(&conn, &thread_id)?;
+
+    let messages = list_messages_by_sequence(&conn, &thread.id)?;
+    let diffs = read_thread_diffs(&root, &thread.id)?;
+    let todo_history = list_todo_events(&conn, &thread.id)?;
+    let archive = new_archive(thread, messages, diffs, todo_history, now_ms());
+    let json = serde_json::to_string_pretty(&archive).map_err(|e| e.to_string())?;
+    write_export(Path::new(&path), &json)
+}
+
+#[tauri::command]
+pub fn export_thread_markdown(
+    app: AppHandle,
+    thread_id: String,
+    path: String,
+) -> Result<(), String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let conn = open_db(&root)?;
+    let thread = require_thread(&conn, &thread_id)?;
+
+    let messages = list_messages_by_sequence(&conn, &thread.id)?;
+    let diffs = read_thread_diffs(&root, &thread.id)?;
+    write_export(
+        Path::new(&path),
+        &render_markdown(&thread, &messages, &diffs),
+    )
+}
+
+#[tauri::command]
+pub fn import_thread(
+    app: AppHandle,
+    project_id: String,
+    path: String,
+) -> Result<ThreadRecord, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let mut conn = open_db(&root)?;
+    let project = require_project(&conn, &project_id)?;
+
+    let archive = read_json_file::<ThreadArchive>(Path::new(&path))?;
+    check_archive(&archive)?;
+    let archive = remap_archive(archive, &project.id, now_ms());
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let saved = insert_thread(
+        &root,
+        &tx,
+        &archive.thread,
+        &archive.messages,
+        &archive.diffs,
+    )
+    .and_then(|()| append_todo_events(&tx, &archive.thread.id, &archive.todo_history))
+    .and_then(|()| tx.commit().map_err(|e| e.to_string()));
+    if let Err(error) = saved {
+        let _ = fs::remove_dir_all(thread_diffs_dir(&root, &archive.thread.id));
+        return Err(error);
+    }
+
+    Ok(archive.thread)
+}
@@ -0,0 +1,310 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{params, Connection};
+
+use crate::core::constants::{
+    AUTO_BACKUP_LABEL, BACKUP_SETTINGS_FILE, DATABASE_FILE, LOCKS_DIR, STORAGE_LOCK_FILE,
+    WORKTREES_DIR,
+};
+use crate::core::models::{BackupInfo, BackupSettings};
+use crate::core::time::now_ms;
+use crate::storage::integrity::verify_storage_root;
+use crate::storage::io::{ensure_storage_ready, prepare_storage, read_json_file, write_json_file};
+use crate::storage::lock::lock_storage_exclusive;
+use crate::storage::paths::{backups_dir, database_file};
+
+fn copy_dir_recursive(from: &Path, to: &Path) -> Result<(), String> {
+    fs::create_dir_all(to).map_err(|e| e.to_string())?;
+    for entry in fs::read_dir(from).map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let source = entry.path();
+        let target = to.join(entry.file_name());
+        if entry.file_type().map_err(|e| e.to_string())?.is_dir() {
+            copy_dir_recursive(&source, &target)?;
+        } else {
+            fs::copy(&source, &target).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+fn is_database_file(name: &str) -> bool {
+    name == DATABASE_FILE
+        || name
+            .strip_prefix(DATABASE_FILE)
+            .is_some_and(|suffix| suffix == "-wal" || suffix == "-shm")
+}
+
+fn is_local_entry(name: &str) -> bool {
+    name == WORKTREES_DIR || name == LOCKS_DIR || name == STORAGE_LOCK_FILE
+}
+
+pub fn backup_storage(root: &Path, label: &str) -> Result<PathBuf, String> {
+    let target = backups_dir(root)?.join(format!("{}-{label}", now_ms()));
+    fs::create_dir_all(&target).map_err(|e| e.to_string())?;
+
+    for entry in fs::read_dir(root).map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let name = entry.file_name();
+        let name_str = name.to_string_lossy();
+        if is_local_entry(&name_str) || is_database_file(&name_str) {
+            continue;
+        }
+        let source = entry.path();
+        let dest = target.join(&name);
+        if entry.file_type().map_err(|e| e.to_string())?.is_dir() {
+            copy_dir_recursive(&source, &dest)?;
+        } else {
+            fs::copy(&source, &dest).map_err(|e| e.to_string())?;
+        }
+    }
+
+    let db = database_file(root);
+    if db.exists() {
+        let conn = Connection::open(&db).map_err(|e| e.to_string())?;
+        let dest = database_file(&target);
+        conn.execute("VACUUM INTO ?1", params![dest.to_string_lossy()])
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(target)
+}
+
+fn parse_backup_name(name: &str) -> Option<(u64, &str)> {
+    let (ts, label) = name.split_once('-')?;
+    Some((ts.parse().ok()?, label))
+}
+
+pub fn list_backup_infos(root: &Path) -> Result<Vec<BackupInfo>, String> {
+    let dir = backups_dir(root)?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(&dir).map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        if !entry.file_type().map_err(|e| e.to_string())?.is_dir() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some((created_at_ms, label)) = parse_backup_name(&name) else {
+            continue;
+        };
+        backups.push(BackupInfo {
+            label: label.to_string(),
+            created_at_ms,
+            size_bytes: dir_size(&entry.path()),
+            id: name,
+        });
+    }
+    backups.sort_by_key(|b| Reverse(b.created_at_ms));
+    Ok(backups)
+}
+
+pub fn backup_path(root: &Path, backup_id: &str) -> Result<PathBuf, String> {
+    if parse_backup_name(backup_id).is_none() || backup_id.contains(['/', '\\']) {
+        return Err(format!("Backup not found: {backup_id}"));
+    }
+    let path = backups_dir(root)?.join(backup_id);
+    if !path.is_dir() {
+        return Err(format!("Backup not found: {backup_id}"));
+    }
+    Ok(path)
+}
+
+// Kept beside the backups so that restoring one does not roll them back.
+pub fn read_backup_settings(root: &Path) -> Result<BackupSettings, String> {
+    let path = backups_dir(root)?.join(BACKUP_SETTINGS_FILE);
+    if !path.exists() {
+        return Ok(BackupSettings::default());
+    }
+    read_json_file(&path)
+}
+
+pub fn write_backup_settings(root: &Path, settings: &BackupSettings) -> Result<(), String> {
+    write_json_file(&backups_dir(root)?.join(BACKUP_SETTINGS_FILE), settings)
+}
+
+pub fn run_scheduled_backup(root: &Path) -> Result<(), String> {
+    let _lock = ensure_storage_ready(root)?;
+    let settings = read_backup_settings(root)?;
+    if settings.keep == 0 {
+        return Ok(());
+    }
+
+    let mut scheduled = list_backup_infos(root)?
+        .into_iter()
+        .filter(|b| b.label == AUTO_BACKUP_LABEL)
+        .collect::<Vec<_>>();
+    let now = now_ms();
+    let due = scheduled
+        .first()
+        .is_none_or(|latest| now.saturating_sub(latest.created_at_ms) >= settings.interval_ms);
+    if due {
+        backup_storage(root, AUTO_BACKUP_LABEL)?;
+        scheduled = list_backup_infos(root)?
+            .into_iter()
+            .filter(|b| b.label == AUTO_BACKUP_LABEL)
+            .collect();
+    }
+
+    for stale in scheduled.iter().skip(settings.keep) {
+        fs::remove_dir_all(backups_dir(root)?.join(&stale.id)).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+pub fn restore_storage(root: &Path, backup_id: &str) -> Result<(), String> {
+    let source = backup_path(root, backup_id)?;
+    let report = verify_storage_root(&source);
+    if !report.ok {
+        let count = report.database_errors.len() + report.unreadable_files.len();
+        return Err(format!(
+            "Backup {backup_id} failed verification with {count} problem(s); not restoring it"
+        ));
+    }
+
+    let _lock = lock_storage_exclusive(root)?;
+    backup_storage(root, "pre-restore")?;
+
+    for entry in fs::read_dir(root).map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        if is_local_entry(&entry.file_name().to_string_lossy()) {
+            continue;
+        }
+        let path = entry.path();
+        if entry.file_type().map_err(|e| e.to_string())?.is_dir() {
+            fs::remove_dir_all(&path).map_err(|e| e.to_string())?;
+        } else {
+            fs::remove_file(&path).map_err(|e| e.to_string())?;
+        }
+    }
+    copy_dir_recursive(&source, root)?;
+
+    // Older backups are brought up to the current schema.
+    prepare_storage(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::core::models::{ProjectRecord, ProjectSettings};
+    use crate::storage::db::{delete_project_row, get_project, open_db, put_project};
+
+    fn setup(name: &str) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("kodit-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let root = dir.join("storage");
+        prepare_storage(&root).unwrap();
+        (dir, root)
+    }
+
+    fn project(id: &str) -> ProjectRecord {
+        ProjectRecord {
+            id: id.to_string(),
+            name: id.to_string(),
+            workspace_path: format!("/{id}"),
+            created_at_ms: 0,
+            updated_at_ms: 0,
+            archived: false,
+            deleted_at_ms: None,
+            settings: ProjectSettings::default(),
+        }
+    }
+
+    #[test]
+    fn scheduled_backups_keep_only_the_newest() {
+        let (dir, root) = setup("backup-rotation");
+        write_backup_settings(
+            &root,
+            &BackupSettings {
+                keep: 2,
+                interval_ms: 0,
+            },
+        )
+        .unwrap();
+        backup_storage(&root, "manual").unwrap();
+
+        let mut created = Vec::new();
+        for _ in 0..3 {
+            // Backup names carry a millisecond timestamp.
+            thread::sleep(Duration::from_millis(2));
+            run_scheduled_backup(&root).unwrap();
+            created.push(list_backup_infos(&root).unwrap()[0].id.clone());
+        }
+
+        let ids = list_backup_infos(&root)
+            .unwrap()
+            .into_iter()
+            .map(|b| (b.id, b.label))
+            .collect::<Vec<_>>();
+        assert_eq!(ids.len(), 3);
+        assert_eq!(ids[0], (created[2].clone(), AUTO_BACKUP_LABEL.to_string()));
+        assert_eq!(ids[1], (created[1].clone(), AUTO_BACKUP_LABEL.to_string()));
+        assert_eq!(ids[2].1, "manual");
+
+        // Not due yet with the default interval.
+        write_backup_settings(
+            &root,
+            &BackupSettings {
+                keep: 2,
+                ..BackupSettings::default()
+            },
+        )
+        .unwrap();
+        run_scheduled_backup(&root).unwrap();
+        assert_eq!(list_backup_infos(&root).unwrap().len(), 3);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn restore_replaces_storage_and_keeps_a_pre_restore_copy() {
+        let (dir, root) = setup("backup-restore");
+        let conn = open_db(&root).unwrap();
+        put_project(&conn, &project("kept")).unwrap();
+        drop(conn);
+        let backup = backup_storage(&root, "manual").unwrap();
+        let backup_id = backup.file_name().unwrap().to_string_lossy().to_string();
+
+        let conn = open_db(&root).unwrap();
+        delete_project_row(&conn, "kept").unwrap();
+        put_project(&conn, &project("later")).unwrap();
+        drop(conn);
+        thread::sleep(Duration::from_millis(2));
+
+        restore_storage(&root, &backup_id).unwrap();
+
+        let conn = open_db(&root).unwrap();
+        assert!(get_project(&conn, "kept").unwrap().is_some());
+        assert!(get_project(&conn, "later").unwrap().is_none());
+        let labels = list_backup_infos(&root)
+            .unwrap()
+            .into_iter()
+            .map(|b| b.label)
+            .collect::<Vec<_>>();
+        assert_eq!(labels, ["pre-restore", "manual"]);
+        assert!(restore_storage(&root, "1-../escape").is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,65 @@
+use tauri::AppHandle;
+
+use crate::core::models::{BackupInfo, BackupSettings, StorageIntegrityReport};
+use crate::storage::backup::{
+    backup_path, backup_storage, list_backup_infos, read_backup_settings, restore_storage,
+    write_backup_settings,
+};
+use crate::storage::integrity::verify_storage_root;
+use crate::storage::io::ensure_storage_ready;
+use crate::storage::lock::lock_storage_shared;
+use crate::storage::paths::storage_root;
+
+#[tauri::command]
+pub fn list_backups(app: AppHandle) -> Result<Vec<BackupInfo>, String> {
+    let root = storage_root(&app)?;
+    // Storage is not prepared here so backups stay reachable after a failed
+    // or too-new migration.
+    let _lock = lock_storage_shared(&root)?;
+    list_backup_infos(&root)
+}
+
+#[tauri::command]
+pub fn create_backup(app: AppHandle) -> Result<Vec<BackupInfo>, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    backup_storage(&root, "manual")?;
+    list_backup_infos(&root)
+}
+
+#[tauri::command]
+pub fn restore_backup(app: AppHandle, backup_id: String) -> Result<(), String> {
+    let root = storage_root(&app)?;
+    // Takes the storage lock exclusively itself.
+    restore_storage(&root, &backup_id)
+}
+
+#[tauri::command]
+pub fn verify_storage(
+    app: AppHandle,
+    backup_id: Option<String>,
+) -> Result<StorageIntegrityReport, String> {
+    let root = storage_root(&app)?;
+    // Like `list_backups`, this must work on storage that cannot be prepared.
+    let _lock = lock_storage_shared(&root)?;
+    match backup_id {
+        Some(id) => Ok(verify_storage_root(&backup_path(&root, &id)?)),
+        None => Ok(verify_storage_root(&root)),
+    }
+}
+
+#[tauri::command]
+pub fn get_backup_settings(app: AppHandle) -> Result<BackupSettings, String> {
+    let root = storage_root(&app)?;
+    read_backup_settings(&root)
+}
+
+#[tauri::command]
+pub fn set_backup_settings(
+    app: AppHandle,
+    settings: BackupSettings,
+) -> Result<BackupSettings, String> {
+    let root = storage_root(&app)?;
+    write_backup_settings(&root, &settings)?;
+    Ok(settings)
+}
@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use std::fs;
+
+use rusqlite::{Connection, TransactionBehavior};
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use crate::core::models::{
+    BranchMessage, DiffRecord, MessageRecord, ThreadBranch, ThreadForkOrigin, ThreadRecord,
+};
+use crate::core::time::now_ms;
+use crate::storage::db::{list_messages_by_sequence, open_db, put_thread, require_thread};
+use crate::storage::io::{ensure_storage_ready, insert_thread, read_thread_diffs};
+use crate::storage::lock::lock_thread;
+use crate::storage::paths::{storage_root, thread_diffs_dir};
+use crate::storage::tree::MessageTree;
+
+fn build_branch(thread: &ThreadRecord, messages: &[MessageRecord]) -> ThreadBranch {
+    let tree = MessageTree::new(messages);
+    let active_leaf = tree.active_leaf(thread.active_leaf_id.as_deref());
+
+    let branch = active_leaf
+        .map(|leaf| tree.path_to(leaf))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|message| BranchMessage {
+            message: message.clone(),
+            sibling_ids: tree
+                .siblings_of(&message.id)
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        })
+        .collect();
+
+    ThreadBranch {
+        thread_id: thread.id.clone(),
+        active_leaf_id: active_leaf.map(str::to_string),
+        messages: branch,
+    }
+}
+
+fn load_branch(conn: &Connection, thread: &ThreadRecord) -> Result<ThreadBranch, String> {
+    let messages = list_messages_by_sequence(conn, &thread.id)?;
+    Ok(build_branch(thread, &messages))
+}
+
+#[tauri::command]
+pub fn get_active_branch(app: AppHandle, thread_id: String) -> Result<ThreadBranch, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let conn = open_db(&root)?;
+    let thread = require_thread(&conn, &thread_id)?;
+
+    load_branch(&conn, &thread)
+}
+
+#[tauri::command]
+pub fn set_active_branch(
+    app: AppHandle,
+    thread_id: String,
+    message_id: String,
+) -> Result<ThreadBranch, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let _thread_lock = lock_thread(&root, &thread_id)?;
+    let conn = open_db(&root)?;
+    let mut thread = require_thread(&conn, &thread_id)?;
+
+    let messages = list_messages_by_sequence(&conn, &thread.id)?;
+    let tree = MessageTree::new(&messages);
+    let message_id = tree
+        .get(&message_id)
+        .map(|message| message.id.as_str())
+        .ok_or_else(|| format!("Message not found: {message_id}"))?;
+
+    thread.active_leaf_id = Some(tree.latest_leaf_under(message_id).to_string());
+    put_thread(&conn, &thread)?;
+    Ok(build_branch(&thread, &messages))
+}
+
+fn fork_records(
+    source: &ThreadRecord,
+    messages: &[MessageRecord],
+    diffs: Vec<DiffRecord>,
+    at_message_id: &str,
+    title: Option<String>,
+    ts: u64,
+) -> Result<(ThreadRecord, Vec<MessageRecord>, Vec<DiffRecord>), String> {
+    let tree = MessageTree::new(messages);
+    if !tree.contains(at_message_id) {
+        return Err(format!("Message not found: {at_message_id}"));
+    }
+
+    let fork_id = Uuid::new_v4().to_string();
+    let path = tree.path_to(at_message_id);
+    let id_map = path
+        .iter()
+        .map(|message| (message.id.clone(), Uuid::new_v4().to_string()))
+        .collect::<HashMap<_, _>>();
+
+    let copies = path
+        .iter()
+        .enumerate()
+        .map(|(index, message)| MessageRecord {
+            id: id_map[&message.id].clone(),
+            thread_id: fork_id.clone(),
+            parent_id: tree
+                .parent_of(&message.id)
+                .filter(|_| index > 0)
+                .map(|p| id_map[p].clone()),
+            sequence: index as u64 + 1,
+            ..(*message).clone()
+        })
+        .collect::<Vec<_>>();
+
+    // Diffs follow the messages that produced them onto the fork.
+    let diff_copies = diffs
+        .into_iter()
+        .filter_map(|diff| {
+            let message_id = diff.message_id.as_ref().and_then(|id| id_map.get(id))?;
+            Some(DiffRecord {
+                id: Uuid::new_v4().to_string(),
+                thread_id: fork_id.clone(),
+                message_id: Some(message_id.clone()),
+                ..diff
+            })
+        })
+        .collect();
+
+    let fork = ThreadRecord {
+        id: fork_id,
+        project_id: source.project_id.clone(),
+        title: title
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(|| format!("{} (fork)", source.title)),
+        mode: source.mode,
+        created_at_ms: ts,
+        updated_at_ms: ts,
+        worktree: None,
+        archived: false,
+        deleted_at_ms: None,
+        active_leaf_id: copies.last().map(|m| m.id.clone()),
+        forked_from: Some(ThreadForkOrigin {
+            thread_id: source.id.clone(),
+            message_id: at_message_id.to_string(),
+        }),
+        todos: Vec::new(),
+    };
+    Ok((fork, copies, diff_copies))
+}
+
+#[tauri::command]
+pub fn fork_thread(
+    app: AppHandle,
+    thread_id: String,
+    at_message_id: String,
+    title: Option<String>,
+) -> Result<ThreadRecord, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let mut conn = open_db(&root)?;
+    let source = require_thread(&conn, &thread_id)?;
+
+    let messages = list_messages_by_sequence(&conn, &source.id)?;
+    let diffs = read_thread_diffs(&root, &source.id)?;
+    let (fork, copies, diff_copies) =
+        fork_records(&source, &messages, diffs, &at_message_id, title, now_ms())?;
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let saved = insert_thread(&root, &tx, &fork, &copies, &diff_copies)
+        .and_then(|()| tx.commit().map_err(|e| e.to_string()));
+    if let Err(error) = saved {
+        let _ = fs::remove_dir_all(thread_diffs_dir(&root, &fork.id));
+        return Err(error);
+    }
+
+    Ok(fork)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{AgentMode, MessageRole};
+
+    fn message(id: &str, parent: Option<&str>, sequence: u64) -> MessageRecord {
+        MessageRecord {
+            id: id.to_string(),
+            thread_id: "t".to_string(),
+            role: MessageRole::User,
+            content: id.to_string(),
+            model: None,
+            provider: None,
+            mode: AgentMode::Build,
+            tokens: Default::default(),
+            parent_id: parent.map(str::to_string),
+            created_at_ms: sequence,
+            updated_at_ms: sequence,
+            sequence,
+            parts: Vec::new(),
+        }
+    }
+
+    fn diff(id: &str, message_id: Option<&str>) -> DiffRecord {
+        DiffRecord {
+            id: id.to_string(),
+            thread_id: "t".to_string(),
+            message_id: message_id.map(str::to_string),
+            summary: None,
+            created_at_ms: 0,
+            files: Vec::new(),
+            hunk_reviews: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn fork_copies_the_path_with_fresh_ids() {
+        let source = ThreadRecord {
+            id: "t".to_string(),
+            project_id: "p".to_string(),
+            title: "Source".to_string(),
+            mode: AgentMode::Plan,
+            created_at_ms: 0,
+            updated_at_ms: 0,
+            worktree: None,
+            archived: false,
+            deleted_at_ms: None,
+            active_leaf_id: Some("d".to_string()),
+            forked_from: None,
+            todos: Vec::new(),
+        };
+        let messages = vec![
+            message("a", None, 1),
+            message("b", Some("a"), 2),
+            message("c", Some("a"), 3),
+            message("d", Some("b"), 4),
+        ];
+        let diffs = vec![
+            diff("d1", Some("b")),
+            diff("d2", Some("c")),
+            diff("d3", Some("d")),
+            diff("d4", None),
+        ];
+
+        let (fork, copies, diff_copies) =
+            fork_records(&source, &messages, diffs, "b", Some("  ".to_string()), 7).unwrap();
+
+        assert_ne!(fork.id, source.id);
+        assert_eq!(fork.title, "Source (fork)");
+        assert_eq!(fork.mode, AgentMode::Plan);
+        assert_eq!(fork.created_at_ms, 7);
+        let origin = fork.forked_from.as_ref().unwrap();
+        assert_eq!(
+            (origin.thread_id.as_str(), origin.message_id.as_str()),
+            ("t", "b")
+        );
+
+        let contents = copies
+            .iter()
+            .map(|m| m.content.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(contents, ["a", "b"]);
+        assert!(copies
+            .iter()
+            .all(|m| m.thread_id == fork.id && m.id != m.content));
+        assert_eq!(copies[0].parent_id, None);
+        assert_eq!(copies[1].parent_id.as_ref(), Some(&copies[0].id));
+        assert_eq!([copies[0].sequence, copies[1].sequence], [1, 2]);
+        assert_eq!(fork.active_leaf_id.as_ref(), Some(&copies[1].id));
+
+        assert_eq!(diff_copies.len(), 1);
+        assert_eq!(diff_copies[0].thread_id, fork.id);
+        assert_eq!(diff_copies[0].message_id.as_ref(), Some(&copies[1].id));
+        assert_ne!(diff_copies[0].id, "d1");
+
+        assert!(fork_records(&source, &messages, Vec::new(), "gone", None, 7).is_err());
+    }
+}
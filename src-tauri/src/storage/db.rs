@@ -0,0 +1,466 @@
+use std::path::Path;
+use std::time::Duration;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::core::models::{MessageRecord, ProjectRecord, ThreadRecord};
+use crate::storage::health::quarantine_row;
+use crate::storage::paths::database_file;
+use crate::storage::search::{index_message, index_thread, unindex_message, unindex_thread};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS projects (
+    id TEXT PRIMARY KEY,
+    workspace_path TEXT NOT NULL,
+    updated_at_ms INTEGER NOT NULL,
+    archived INTEGER NOT NULL DEFAULT 0,
+    deleted_at_ms INTEGER,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS projects_updated ON projects (updated_at_ms DESC);
+
+CREATE TABLE IF NOT EXISTS threads (
+    id TEXT PRIMARY KEY,
+    project_id TEXT NOT NULL,
+    updated_at_ms INTEGER NOT NULL,
+    archived INTEGER NOT NULL DEFAULT 0,
+    deleted_at_ms INTEGER,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS threads_project_updated ON threads (project_id, updated_at_ms DESC);
+
+CREATE TABLE IF NOT EXISTS messages (
+    id TEXT PRIMARY KEY,
+    thread_id TEXT NOT NULL,
+    sequence INTEGER NOT NULL,
+    created_at_ms INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE UNIQUE INDEX IF NOT EXISTS messages_thread_sequence ON messages (thread_id, sequence);
+CREATE INDEX IF NOT EXISTS messages_thread_created ON messages (thread_id, created_at_ms);
+
+CREATE TABLE IF NOT EXISTS search_docs (
+    id INTEGER PRIMARY KEY,
+    kind TEXT NOT NULL,
+    record_id TEXT NOT NULL,
+    thread_id TEXT NOT NULL,
+    UNIQUE (kind, record_id)
+);
+CREATE INDEX IF NOT EXISTS search_docs_thread ON search_docs (thread_id, kind);
+CREATE TABLE IF NOT EXISTS todo_events (
+    id INTEGER PRIMARY KEY,
+    thread_id TEXT NOT NULL,
+    at_ms INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS todo_events_thread ON todo_events (thread_id, id);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5 (
+    body,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+";
+
+fn to_data<T: Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string(value).map_err(|e| e.to_string())
+}
+
+fn from_data<T: DeserializeOwned>(data: &str) -> Result<T, String> {
+    serde_json::from_str::<T>(data).map_err(|e| e.to_string())
+}
+
+// Rows that no longer parse are skipped; only `repair_storage` removes them.
+fn query_records<T: DeserializeOwned, P: rusqlite::Params>(
+    conn: &Connection,
+    sql: &str,
+    params: P,
+) -> Result<Vec<T>, String> {
+    let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params, |row| row.get::<_, String>(1))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows
+        .iter()
+        .filter_map(|data| from_data::<T>(data).ok())
+        .collect())
+}
+
+pub struct UnreadableRow {
+    pub table: &'static str,
+    pub id: String,
+    pub data: String,
+    pub error: String,
+}
+
+fn find_unreadable<T: DeserializeOwned>(
+    conn: &Connection,
+    table: &'static str,
+    out: &mut Vec<UnreadableRow>,
+) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT id, data FROM {table}"))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| e.to_string())?;
+    for row in rows {
+        let (id, data) = row.map_err(|e| e.to_string())?;
+        if let Err(error) = from_data::<T>(&data) {
+            out.push(UnreadableRow {
+                table,
+                id,
+                data,
+                error,
+            });
+        }
+    }
+    Ok(())
+}
+
+pub fn list_unreadable_rows(conn: &Connection) -> Result<Vec<UnreadableRow>, String> {
+    let mut out = Vec::new();
+    find_unreadable::<ProjectRecord>(conn, "projects", &mut out)?;
+    find_unreadable::<ThreadRecord>(conn, "threads", &mut out)?;
+    find_unreadable::<MessageRecord>(conn, "messages", &mut out)?;
+    Ok(out)
+}
+
+pub fn quarantine_unreadable_rows(root: &Path, conn: &Connection) -> Result<usize, String> {
+    let rows = list_unreadable_rows(conn)?;
+    for row in &rows {
+        quarantine_row(root, row.table, &row.id, &row.data, &row.error)?;
+        conn.execute(
+            &format!("DELETE FROM {} WHERE id = ?1", row.table),
+            params![row.id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(rows.len())
+}
+
+fn query_record<T: DeserializeOwned, P: rusqlite::Params>(
+    conn: &Connection,
+    sql: &str,
+    params: P,
+) -> Result<Option<T>, String> {
+    let data = conn
+        .query_row(sql, params, |row| row.get::<_, String>(0))
+        .optional()
+        .map_err(|e| e.to_string())?;
+    data.map(|d| from_data::<T>(&d)).transpose()
+}
+
+pub fn open_db(root: &Path) -> Result<Connection, String> {
+    let conn = Connection::open(database_file(root)).map_err(|e| e.to_string())?;
+    conn.busy_timeout(Duration::from_secs(5))
+        .map_err(|e| e.to_string())?;
+    conn.execute_batch("PRAGMA synchronous = NORMAL;")
+        .map_err(|e| e.to_string())?;
+    Ok(conn)
+}
+
+// Callers must hold the exclusive storage lock.
+pub fn create_schema(root: &Path) -> Result<(), String> {
+    let conn = open_db(root)?;
+    conn.execute_batch("PRAGMA journal_mode = WAL;")
+        .map_err(|e| e.to_string())?;
+    conn.execute_batch(SCHEMA).map_err(|e| e.to_string())
+}
+
+pub fn get_project(conn: &Connection, project_id: &str) -> Result<Option<ProjectRecord>, String> {
+    query_record(
+        conn,
+        "SELECT data FROM projects WHERE id = ?1",
+        params![project_id],
+    )
+}
+
+pub fn require_project(conn: &Connection, project_id: &str) -> Result<ProjectRecord, String> {
+    get_project(conn, project_id)?
+        .filter(|p| p.deleted_at_ms.is_none())
+        .ok_or_else(|| format!("Project not found: {project_id}"))
+}
+
+pub fn put_project(conn: &Connection, project: &ProjectRecord) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO projects (id, workspace_path, updated_at_ms, archived, deleted_at_ms, data)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT (id) DO UPDATE SET
+            workspace_path = excluded.workspace_path,
+            updated_at_ms = excluded.updated_at_ms,
+            archived = excluded.archived,
+            deleted_at_ms = excluded.deleted_at_ms,
+            data = excluded.data",
+        params![
+            project.id,
+            project.workspace_path,
+            project.updated_at_ms as i64,
+            project.archived,
+            project.deleted_at_ms.map(|ts| ts as i64),
+            to_data(project)?
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn list_project_records(
+    conn: &Connection,
+    archived: bool,
+) -> Result<Vec<ProjectRecord>, String> {
+    query_records(
+        conn,
+        "SELECT id, data FROM projects WHERE deleted_at_ms IS NULL AND archived = ?1
+         ORDER BY updated_at_ms DESC",
+        params![archived],
+    )
+}
+
+pub fn list_all_projects(conn: &Connection) -> Result<Vec<ProjectRecord>, String> {
+    query_records(conn, "SELECT id, data FROM projects", [])
+}
+
+pub fn find_projects_by_workspace(
+    conn: &Connection,
+    workspace_path: &str,
+) -> Result<Vec<ProjectRecord>, String> {
+    query_records(
+        conn,
+        "SELECT id, data FROM projects WHERE workspace_path = ?1 AND deleted_at_ms IS NULL",
+        params![workspace_path],
+    )
+}
+
+pub fn list_trashed_projects(conn: &Connection) -> Result<Vec<ProjectRecord>, String> {
+    query_records(
+        conn,
+        "SELECT id, data FROM projects WHERE deleted_at_ms IS NOT NULL ORDER BY deleted_at_ms DESC",
+        [],
+    )
+}
+
+pub fn delete_project_row(conn: &Connection, project_id: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM projects WHERE id = ?1", params![project_id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn get_thread(conn: &Connection, thread_id: &str) -> Result<Option<ThreadRecord>, String> {
+    query_record(
+        conn,
+        "SELECT data FROM threads WHERE id = ?1",
+        params![thread_id],
+    )
+}
+
+pub fn require_thread(conn: &Connection, thread_id: &str) -> Result<ThreadRecord, String> {
+    get_thread(conn, thread_id)?
+        .filter(|t| t.deleted_at_ms.is_none())
+        .ok_or_else(|| format!("Thread not found: {thread_id}"))
+}
+
+pub fn put_thread(conn: &Connection, thread: &ThreadRecord) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO threads (id, project_id, updated_at_ms, archived, deleted_at_ms, data)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT (id) DO UPDATE SET
+            project_id = excluded.project_id,
+            updated_at_ms = excluded.updated_at_ms,
+            archived = excluded.archived,
+            deleted_at_ms = excluded.deleted_at_ms,
+            data = excluded.data",
+        params![
+            thread.id,
+            thread.project_id,
+            thread.updated_at_ms as i64,
+            thread.archived,
+            thread.deleted_at_ms.map(|ts| ts as i64),
+            to_data(thread)?
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    index_thread(conn, thread)
+}
+
+pub fn list_thread_records(
+    conn: &Connection,
+    project_id: &str,
+    archived: bool,
+) -> Result<Vec<ThreadRecord>, String> {
+    query_records(
+        conn,
+        "SELECT id, data FROM threads
+         WHERE project_id = ?1 AND deleted_at_ms IS NULL AND archived = ?2
+         ORDER BY updated_at_ms DESC",
+        params![project_id, archived],
+    )
+}
+
+pub fn list_all_threads(conn: &Connection) -> Result<Vec<ThreadRecord>, String> {
+    query_records(conn, "SELECT id, data FROM threads", [])
+}
+
+pub fn list_all_project_threads(
+    conn: &Connection,
+    project_id: &str,
+) -> Result<Vec<ThreadRecord>, String> {
+    query_records(
+        conn,
+        "SELECT id, data FROM threads WHERE project_id = ?1",
+        params![project_id],
+    )
+}
+
+pub fn list_trashed_threads(conn: &Connection) -> Result<Vec<ThreadRecord>, String> {
+    query_records(
+        conn,
+        "SELECT id, data FROM threads WHERE deleted_at_ms IS NOT NULL ORDER BY deleted_at_ms DESC",
+        [],
+    )
+}
+
+pub fn delete_thread_rows(conn: &Connection, thread_id: &str) -> Result<(), String> {
+    unindex_thread(conn, thread_id)?;
+    conn.execute("DELETE FROM messages WHERE thread_id = ?1", params![thread_id])
+        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM todo_events WHERE thread_id = ?1", params![thread_id])
+        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM threads WHERE id = ?1", params![thread_id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn next_message_sequence(conn: &Connection, thread_id: &str) -> Result<u64, String> {
+    let last = conn
+        .query_row(
+            "SELECT MAX(sequence) FROM messages WHERE thread_id = ?1",
+            params![thread_id],
+            |row| row.get::<_, Option<i64>>(0),
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(last.map(|s| s as u64 + 1).unwrap_or(1))
+}
+
+pub fn put_message(conn: &Connection, message: &MessageRecord) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO messages (id, thread_id, sequence, created_at_ms, data) VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT (id) DO UPDATE SET
+            thread_id = excluded.thread_id,
+            sequence = excluded.sequence,
+            created_at_ms = excluded.created_at_ms,
+            data = excluded.data",
+        params![
+            message.id,
+            message.thread_id,
+            message.sequence as i64,
+            message.created_at_ms as i64,
+            to_data(message)?
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    index_message(conn, message)
+}
+
+pub fn get_message(
+    conn: &Connection,
+    thread_id: &str,
+    message_id: &str,
+) -> Result<Option<MessageRecord>, String> {
+    query_record(
+        conn,
+        "SELECT data FROM messages WHERE id = ?1 AND thread_id = ?2",
+        params![message_id, thread_id],
+    )
+}
+
+pub fn delete_message_rows_after(
+    conn: &Connection,
+    thread_id: &str,
+    sequence: u64,
+) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id FROM messages WHERE thread_id = ?1 AND sequence > ?2 ORDER BY sequence")
+        .map_err(|e| e.to_string())?;
+    let ids = stmt
+        .query_map(params![thread_id, sequence as i64], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "DELETE FROM messages WHERE thread_id = ?1 AND sequence > ?2",
+        params![thread_id, sequence as i64],
+    )
+    .map_err(|e| e.to_string())?;
+    for id in &ids {
+        unindex_message(conn, id)?;
+    }
+    Ok(ids)
+}
+
+pub fn last_message_id(conn: &Connection, thread_id: &str) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT id FROM messages WHERE thread_id = ?1 ORDER BY sequence DESC LIMIT 1",
+        params![thread_id],
+        |row| row.get::<_, String>(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+pub fn append_message(conn: &Connection, message: &MessageRecord) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO messages (id, thread_id, sequence, created_at_ms, data) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            message.id,
+            message.thread_id,
+            message.sequence as i64,
+            message.created_at_ms as i64,
+            to_data(message)?
+        ],
+    )
+    .map_err(|e| match e.sqlite_error_code() {
+        Some(rusqlite::ErrorCode::ConstraintViolation) => {
+            format!("Message already exists: {}", message.id)
+        }
+        _ => e.to_string(),
+    })?;
+    index_message(conn, message)
+}
+
+pub fn list_message_page(
+    conn: &Connection,
+    thread_id: &str,
+    before_sequence: Option<u64>,
+    limit: usize,
+) -> Result<Vec<MessageRecord>, String> {
+    let mut page = query_records::<MessageRecord, _>(
+        conn,
+        "SELECT id, data FROM messages WHERE thread_id = ?1 AND sequence < ?2
+         ORDER BY sequence DESC LIMIT ?3",
+        params![
+            thread_id,
+            before_sequence.map(|s| s as i64).unwrap_or(i64::MAX),
+            limit as i64
+        ],
+    )?;
+    page.reverse();
+    Ok(page)
+}
+
+pub fn list_messages_by_sequence(
+    conn: &Connection,
+    thread_id: &str,
+) -> Result<Vec<MessageRecord>, String> {
+    query_records(
+        conn,
+        "SELECT id, data FROM messages WHERE thread_id = ?1 ORDER BY sequence",
+        params![thread_id],
+    )
+}
@@ -0,0 +1,137 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use serde_json::Value;
+
+use crate::core::constants::{QUARANTINE_LOG_FILE, WORKTREES_DIR};
+use crate::core::models::QuarantinedFile;
+use crate::core::time::now_ms;
+use crate::storage::io::{read_json_file, write_json_file};
+use crate::storage::paths::corrupt_dir;
+
+fn relative(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
+fn quarantine_target(root: &Path, rel: &str) -> PathBuf {
+    let target = corrupt_dir(root).join(rel);
+    if !target.exists() {
+        return target;
+    }
+    let mut name = target.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}", now_ms()));
+    target.with_file_name(name)
+}
+
+fn log_quarantine(root: &Path, entry: QuarantinedFile) -> Result<(), String> {
+    let log = corrupt_dir(root).join(QUARANTINE_LOG_FILE);
+    let mut entries = if log.exists() {
+        read_json_file::<Vec<QuarantinedFile>>(&log).unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    entries.push(entry);
+    write_json_file(&log, &entries)
+}
+
+pub fn quarantine_file(root: &Path, path: &Path, error: &str) -> Result<(), String> {
+    let rel = relative(root, path);
+    let target = quarantine_target(root, &rel);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::rename(path, &target).map_err(|e| e.to_string())?;
+    log_quarantine(
+        root,
+        QuarantinedFile {
+            original_path: rel,
+            quarantined_path: relative(root, &target),
+            error: error.to_string(),
+            quarantined_at_ms: now_ms(),
+        },
+    )
+}
+
+pub fn quarantine_row(
+    root: &Path,
+    table: &str,
+    id: &str,
+    data: &str,
+    error: &str,
+) -> Result<(), String> {
+    let rel = format!("db/{table}/{id}.json");
+    let target = quarantine_target(root, &rel);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(&target, data).map_err(|e| e.to_string())?;
+    log_quarantine(
+        root,
+        QuarantinedFile {
+            original_path: format!("{table}/{id}"),
+            quarantined_path: relative(root, &target),
+            error: error.to_string(),
+            quarantined_at_ms: now_ms(),
+        },
+    )
+}
+
+pub fn list_quarantined(root: &Path) -> Result<Vec<QuarantinedFile>, String> {
+    let log = corrupt_dir(root).join(QUARANTINE_LOG_FILE);
+    if !log.exists() {
+        return Ok(Vec::new());
+    }
+    read_json_file(&log)
+}
+
+fn find_tmp_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if entry.file_name() != WORKTREES_DIR {
+                find_tmp_files(&path, out);
+            }
+        } else if path.extension().and_then(|e| e.to_str()) == Some("tmp") {
+            out.push(path);
+        }
+    }
+}
+
+pub fn cleanup_tmp_files(
+    root: &Path,
+    min_age: Duration,
+) -> Result<(Vec<String>, Vec<String>), String> {
+    let mut found = Vec::new();
+    find_tmp_files(root, &mut found);
+
+    let now = SystemTime::now();
+    let mut recovered = Vec::new();
+    let mut removed = Vec::new();
+    for tmp in found {
+        let age = fs::metadata(&tmp)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .unwrap_or_default();
+        if age < min_age {
+            continue;
+        }
+
+        let target = tmp.with_extension("json");
+        if !target.exists() && read_json_file::<Value>(&tmp).is_ok() {
+            fs::rename(&tmp, &target).map_err(|e| e.to_string())?;
+            recovered.push(relative(root, &target));
+        } else {
+            fs::remove_file(&tmp).map_err(|e| e.to_string())?;
+            removed.push(relative(root, &tmp));
+        }
+    }
+    Ok((recovered, removed))
+}
@@ -0,0 +1,53 @@
+use std::fs;
+use std::path::Path;
+
+use rusqlite::Connection;
+
+use crate::core::constants::{LEGACY_DIR, LEGACY_JSON_DIRS, PROJECTS_DIR, THREADS_DIR};
+use crate::core::models::{MessageRecord, ProjectRecord, ThreadRecord};
+use crate::storage::db::{put_message, put_project, put_thread};
+use crate::storage::health::quarantine_file;
+use crate::storage::io::{read_json_file, read_json_list};
+use crate::storage::paths::messages_file;
+
+pub fn import_legacy_json(root: &Path, conn: &mut Connection) -> Result<(), String> {
+    if !LEGACY_JSON_DIRS.iter().any(|dir| root.join(dir).exists()) {
+        return Ok(());
+    }
+
+    let projects = read_json_list::<ProjectRecord>(root, &root.join(PROJECTS_DIR))?;
+    let threads = read_json_list::<ThreadRecord>(root, &root.join(THREADS_DIR))?;
+    let mut messages = Vec::new();
+    for thread in &threads {
+        let file = messages_file(root, &thread.id);
+        if !file.exists() {
+            continue;
+        }
+        match read_json_file::<Vec<MessageRecord>>(&file) {
+            Ok(records) => messages.extend(records),
+            Err(error) => quarantine_file(root, &file, &error)?,
+        }
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for project in &projects {
+        put_project(&tx, project)?;
+    }
+    for thread in &threads {
+        put_thread(&tx, thread)?;
+    }
+    for message in &messages {
+        put_message(&tx, message)?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    let legacy = root.join(LEGACY_DIR);
+    fs::create_dir_all(&legacy).map_err(|e| e.to_string())?;
+    for dir in LEGACY_JSON_DIRS {
+        let from = root.join(dir);
+        if from.exists() {
+            fs::rename(&from, legacy.join(dir)).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
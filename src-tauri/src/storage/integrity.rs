@@ -0,0 +1,114 @@
+use std::fs;
+use std::path::Path;
+
+use rusqlite::{Connection, OpenFlags};
+use serde::de::DeserializeOwned;
+
+use crate::core::constants::{DIFFS_DIR, MANIFEST_FILE, MESSAGES_DIR, PROJECTS_DIR, THREADS_DIR};
+use crate::core::models::{
+    DiffRecord, MessageRecord, ProjectRecord, StorageIntegrityReport, StorageManifest,
+    ThreadRecord, UnreadableFile,
+};
+use crate::storage::paths::database_file;
+
+fn check_json<T: DeserializeOwned>(root: &Path, path: &Path, out: &mut Vec<UnreadableFile>) {
+    let result = fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| serde_json::from_str::<T>(&content).map_err(|e| e.to_string()));
+    if let Err(error) = result {
+        out.push(UnreadableFile {
+            path: path
+                .strip_prefix(root)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .to_string(),
+            error,
+        });
+    }
+}
+
+fn check_json_dir<T: DeserializeOwned>(root: &Path, dir: &Path, out: &mut Vec<UnreadableFile>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            check_json_dir::<T>(root, &path, out);
+        } else if path.extension().and_then(|e| e.to_str()) == Some("json") {
+            check_json::<T>(root, &path, out);
+        }
+    }
+}
+
+fn check_rows<T: DeserializeOwned>(
+    conn: &Connection,
+    table: &str,
+    out: &mut Vec<String>,
+) -> Result<(), rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!("SELECT id, data FROM {table}"))?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+    for row in rows {
+        let (id, data) = row?;
+        if let Err(e) = serde_json::from_str::<T>(&data) {
+            out.push(format!("{table}/{id}: {e}"));
+        }
+    }
+    Ok(())
+}
+
+fn check_database(path: &Path) -> Vec<String> {
+    let mut errors = Vec::new();
+    let conn = match Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY) {
+        Ok(conn) => conn,
+        Err(e) => return vec![e.to_string()],
+    };
+
+    let result = (|| {
+        let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        for row in rows {
+            let row = row?;
+            if row != "ok" {
+                errors.push(row);
+            }
+        }
+        check_rows::<ProjectRecord>(&conn, "projects", &mut errors)?;
+        check_rows::<ThreadRecord>(&conn, "threads", &mut errors)?;
+        check_rows::<MessageRecord>(&conn, "messages", &mut errors)
+    })();
+    if let Err(e) = result {
+        errors.push(e.to_string());
+    }
+    errors
+}
+
+pub fn verify_storage_root(root: &Path) -> StorageIntegrityReport {
+    let mut unreadable_files = Vec::new();
+
+    let manifest = root.join(MANIFEST_FILE);
+    if manifest.exists() {
+        check_json::<StorageManifest>(root, &manifest, &mut unreadable_files);
+    }
+    check_json_dir::<DiffRecord>(root, &root.join(DIFFS_DIR), &mut unreadable_files);
+
+    // Only present in storage that predates the SQLite store.
+    check_json_dir::<ProjectRecord>(root, &root.join(PROJECTS_DIR), &mut unreadable_files);
+    check_json_dir::<ThreadRecord>(root, &root.join(THREADS_DIR), &mut unreadable_files);
+    check_json_dir::<Vec<MessageRecord>>(root, &root.join(MESSAGES_DIR), &mut unreadable_files);
+
+    let db = database_file(root);
+    let database_errors = if db.exists() {
+        check_database(&db)
+    } else {
+        Vec::new()
+    };
+
+    StorageIntegrityReport {
+        ok: database_errors.is_empty() && unreadable_files.is_empty(),
+        database_errors,
+        unreadable_files,
+    }
+}
@@ -1,11 +1,17 @@
 use serde::Serialize;
+use rusqlite::Connection;
 use serde::de::DeserializeOwned;
 use std::fs;
 use std::path::Path;
 
-use crate::core::constants::{DIFFS_DIR, MANIFEST_FILE, MESSAGES_DIR, PROJECTS_DIR, SCHEMA_VERSION, THREADS_DIR};
-use crate::core::models::{ProjectRecord, StorageManifest, ThreadRecord};
-use crate::storage::paths::{project_file, thread_file};
+use crate::core::constants::{DIFFS_DIR, LEGACY_JSON_DIRS, MANIFEST_FILE, SCHEMA_VERSION};
+use crate::core::models::{DiffRecord, MessageRecord, StorageManifest, ThreadRecord};
+use crate::storage::db::{append_message, create_schema, put_thread};
+use crate::storage::health::quarantine_file;
+use crate::storage::lock::{lock_storage_exclusive, lock_storage_shared, StorageLock};
+use crate::storage::migrations::run_migrations;
+use crate::storage::paths::{database_file, diff_file, thread_diffs_dir};
+use crate::storage::search::index_diff;
 use crate::core::time::now_ms;
 
 pub fn read_json_file<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
@@ -30,7 +36,7 @@ pub fn write_json_file<T: Serialize>(path: &Path, value: &T) -> Result<(), Strin
     Ok(())
 }
 
-pub fn read_json_list<T: DeserializeOwned>(dir: &Path) -> Result<Vec<T>, String> {
+pub fn read_json_list<T: DeserializeOwned>(root: &Path, dir: &Path) -> Result<Vec<T>, String> {
     if !dir.exists() {
         return Ok(Vec::new());
     }
@@ -43,48 +49,79 @@ pub fn read_json_list<T: DeserializeOwned>(dir: &Path) -> Result<Vec<T>, String>
         if path.extension().and_then(|e| e.to_str()) != Some("json") {
             continue;
         }
-        out.push(read_json_file::<T>(&path)?);
+        match read_json_file::<T>(&path) {
+            Ok(value) => out.push(value),
+            Err(error) => quarantine_file(root, &path, &error)?,
+        }
     }
     Ok(out)
 }
 
-pub fn ensure_storage_ready(root: &Path) -> Result<(), String> {
+fn storage_needs_setup(root: &Path) -> bool {
+    let current = read_json_file::<StorageManifest>(&root.join(MANIFEST_FILE))
+        .is_ok_and(|m| m.schema_version == SCHEMA_VERSION);
+    !current || !database_file(root).exists() || !root.join(DIFFS_DIR).exists()
+}
+
+pub fn ensure_storage_ready(root: &Path) -> Result<StorageLock, String> {
     fs::create_dir_all(root).map_err(|e| e.to_string())?;
-    fs::create_dir_all(root.join(PROJECTS_DIR)).map_err(|e| e.to_string())?;
-    fs::create_dir_all(root.join(THREADS_DIR)).map_err(|e| e.to_string())?;
-    fs::create_dir_all(root.join(MESSAGES_DIR)).map_err(|e| e.to_string())?;
+    let lock = lock_storage_shared(root)?;
+    if !storage_needs_setup(root) {
+        return Ok(lock);
+    }
+    drop(lock);
+
+    let lock = lock_storage_exclusive(root)?;
+    prepare_storage(root)?;
+    lock.downgrade()
+}
+
+// Callers must hold the exclusive storage lock.
+pub fn prepare_storage(root: &Path) -> Result<(), String> {
     fs::create_dir_all(root.join(DIFFS_DIR)).map_err(|e| e.to_string())?;
 
     let manifest_path = root.join(MANIFEST_FILE);
-    if manifest_path.exists() {
-        let mut manifest = read_json_file::<StorageManifest>(&manifest_path)?;
-        manifest.updated_at_ms = now_ms();
-        write_json_file(&manifest_path, &manifest)?;
+    let mut manifest = if manifest_path.exists() {
+        read_json_file::<StorageManifest>(&manifest_path)?
     } else {
+        // Storage from before the manifest existed is treated as the first schema.
+        let has_legacy_data = LEGACY_JSON_DIRS.iter().any(|dir| root.join(dir).exists());
         let ts = now_ms();
-        let manifest = StorageManifest {
-            schema_version: SCHEMA_VERSION,
+        StorageManifest {
+            schema_version: if has_legacy_data { 1 } else { SCHEMA_VERSION },
             created_at_ms: ts,
             updated_at_ms: ts,
-        };
-        write_json_file(&manifest_path, &manifest)?;
-    }
+        }
+    };
+
+    run_migrations(root, &mut manifest)?;
+    create_schema(root)?;
 
+    manifest.updated_at_ms = now_ms();
+    write_json_file(&manifest_path, &manifest)?;
     Ok(())
 }
 
-pub fn require_project(root: &Path, project_id: &str) -> Result<ProjectRecord, String> {
-    let path = project_file(root, project_id);
-    if !path.exists() {
-        return Err(format!("Project not found: {project_id}"));
-    }
-    read_json_file::<ProjectRecord>(&path)
+pub fn read_thread_diffs(root: &Path, thread_id: &str) -> Result<Vec<DiffRecord>, String> {
+    let mut diffs = read_json_list::<DiffRecord>(root, &thread_diffs_dir(root, thread_id))?;
+    diffs.sort_by_key(|d| d.created_at_ms);
+    Ok(diffs)
 }
 
-pub fn require_thread(root: &Path, thread_id: &str) -> Result<ThreadRecord, String> {
-    let path = thread_file(root, thread_id);
-    if !path.exists() {
-        return Err(format!("Thread not found: {thread_id}"));
+pub fn insert_thread(
+    root: &Path,
+    conn: &Connection,
+    thread: &ThreadRecord,
+    messages: &[MessageRecord],
+    diffs: &[DiffRecord],
+) -> Result<(), String> {
+    put_thread(conn, thread)?;
+    for message in messages {
+        append_message(conn, message)?;
+    }
+    for diff in diffs {
+        write_json_file(&diff_file(root, &diff.thread_id, &diff.id), diff)?;
+        index_diff(conn, diff)?;
     }
-    read_json_file::<ThreadRecord>(&path)
+    Ok(())
 }
@@ -0,0 +1,147 @@
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::core::constants::{
+    LOCK_RETRY_INTERVAL_MS, STORAGE_LOCK_TIMEOUT_MS, THREAD_LOCK_TIMEOUT_MS,
+};
+use crate::storage::paths::{storage_lock_file, thread_lock_file};
+
+pub struct StorageLock {
+    file: File,
+}
+
+impl StorageLock {
+    // The lock is briefly released in between.
+    pub fn downgrade(self) -> Result<StorageLock, String> {
+        self.file.unlock().map_err(|e| e.to_string())?;
+        self.file.lock_shared().map_err(|e| e.to_string())?;
+        Ok(self)
+    }
+}
+
+fn open_lock_file(path: &Path) -> Result<File, String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+        .map_err(|e| e.to_string())
+}
+
+fn acquire(
+    path: &Path,
+    exclusive: bool,
+    timeout: Duration,
+    busy: impl FnOnce() -> String,
+) -> Result<StorageLock, String> {
+    let file = open_lock_file(path)?;
+    let deadline = Instant::now() + timeout;
+    loop {
+        let attempt = if exclusive {
+            file.try_lock()
+        } else {
+            file.try_lock_shared()
+        };
+        match attempt {
+            Ok(()) => return Ok(StorageLock { file }),
+            Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                thread::sleep(Duration::from_millis(LOCK_RETRY_INTERVAL_MS));
+            }
+            Err(TryLockError::WouldBlock) => return Err(busy()),
+            Err(TryLockError::Error(e)) => return Err(e.to_string()),
+        }
+    }
+}
+
+pub fn lock_storage_shared(root: &Path) -> Result<StorageLock, String> {
+    acquire(
+        &storage_lock_file(root),
+        false,
+        Duration::from_millis(STORAGE_LOCK_TIMEOUT_MS),
+        || {
+            "Storage is being migrated or restored by another Kodit window; try again shortly"
+                .to_string()
+        },
+    )
+}
+
+pub fn lock_storage_exclusive(root: &Path) -> Result<StorageLock, String> {
+    acquire(
+        &storage_lock_file(root),
+        true,
+        Duration::from_millis(STORAGE_LOCK_TIMEOUT_MS),
+        || "Storage is in use by another Kodit window; close it and try again".to_string(),
+    )
+}
+
+pub fn lock_thread(root: &Path, thread_id: &str) -> Result<StorageLock, String> {
+    if thread_id.is_empty() || thread_id.contains(['/', '\\', '.']) {
+        return Err(format!("Thread not found: {thread_id}"));
+    }
+    acquire(
+        &thread_lock_file(root, thread_id),
+        true,
+        Duration::from_millis(THREAD_LOCK_TIMEOUT_MS),
+        || {
+            format!(
+                "Thread {thread_id} is being changed by another Kodit window; try again shortly"
+            )
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn try_acquire(path: &Path, exclusive: bool) -> Result<StorageLock, String> {
+        acquire(path, exclusive, Duration::from_millis(50), || {
+            "busy".to_string()
+        })
+    }
+
+    #[test]
+    fn shared_locks_exclude_only_exclusive_ones() {
+        let root = std::env::temp_dir().join(format!("kodit-lock-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let path = storage_lock_file(&root);
+
+        let first = lock_storage_shared(&root).unwrap();
+        let second = lock_storage_shared(&root).unwrap();
+        assert_eq!(try_acquire(&path, true).err().as_deref(), Some("busy"));
+        drop((first, second));
+
+        let exclusive = lock_storage_exclusive(&root).unwrap();
+        assert!(try_acquire(&path, false).is_err());
+        assert!(try_acquire(&path, true).is_err());
+
+        let shared = exclusive.downgrade().unwrap();
+        assert!(try_acquire(&path, false).is_ok());
+        assert!(try_acquire(&path, true).is_err());
+        drop(shared);
+        assert!(try_acquire(&path, true).is_ok());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn thread_locks_are_per_thread() {
+        let root = std::env::temp_dir().join(format!("kodit-thread-lock-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+
+        let held = lock_thread(&root, "a").unwrap();
+        assert!(try_acquire(&thread_lock_file(&root, "a"), true).is_err());
+        assert!(lock_thread(&root, "b").is_ok());
+        drop(held);
+        assert!(lock_thread(&root, "a").is_ok());
+        assert!(lock_thread(&root, "../a").is_err());
+        assert!(lock_thread(&root, "").is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -1,35 +1,72 @@
+use std::collections::HashSet;
 use std::fs;
 
+use rusqlite::TransactionBehavior;
 use tauri::AppHandle;
 use uuid::Uuid;
 
+use crate::core::workspace::canonicalize_workspace;
+use crate::core::message_parts::parts_from_content;
 use crate::core::models::{
-    AgentMode, DiffCreateInput, DiffRecord, FileChangeType, FileSnapshotChange, MessageCreateInput,
-    MessageRecord,
+    AgentMode, DeletedMessages, DiffCreateInput, DiffRecord, FileSnapshotChange, LinkedDiffAction,
+    MessageCreateInput, MessageRecord, MessageRole, MessageUpdateInput,
 };
-use crate::storage::io::{
-    ensure_storage_ready, read_json_file, read_json_list, require_thread, write_json_file,
+use crate::diff::revert::revert_diffs;
+use crate::diff::squash::classify_change;
+use crate::git::worktree::thread_workspace_path;
+use crate::core::constants::{MAX_MESSAGE_PAGE_SIZE, MESSAGE_PAGE_SIZE};
+use crate::storage::db::{
+    append_message, delete_message_rows_after, get_message,
+    last_message_id, list_message_page, list_messages_by_sequence,
+    next_message_sequence, open_db, put_message, put_thread, require_project, require_thread,
 };
-use crate::storage::paths::{diff_file, messages_file, storage_root, thread_diffs_dir, thread_file};
+use crate::storage::io::{ensure_storage_ready, read_thread_diffs, write_json_file};
+use crate::storage::lock::lock_thread;
+use crate::storage::paths::{diff_file, storage_root, thread_diffs_dir};
+use crate::storage::search::{index_diff, unindex_diff, unindex_thread_diffs};
+use crate::storage::usage::enforce_budgets;
 use crate::core::time::now_ms;
 
 #[tauri::command]
 pub fn add_message(app: AppHandle, input: MessageCreateInput) -> Result<MessageRecord, String> {
     let root = storage_root(&app)?;
-    ensure_storage_ready(&root)?;
-
-    let mut thread = require_thread(&root, &input.thread_id)?;
-    let file = messages_file(&root, &thread.id);
-    let mut messages = if file.exists() {
-        read_json_file::<Vec<MessageRecord>>(&file)?
-    } else {
-        Vec::new()
-    };
+    let _lock = ensure_storage_ready(&root)?;
+    let _thread_lock = lock_thread(&root, &input.thread_id)?;
+
+    let mut conn = open_db(&root)?;
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
 
+    let mut thread = require_thread(&tx, &input.thread_id)?;
+    // A new user turn is where spending starts, so blocking budgets stop it here.
+    if matches!(input.role, MessageRole::User) {
+        enforce_budgets(&root, &tx, &thread.project_id, &thread.id)?;
+    }
     let ts = input.created_at_ms.unwrap_or_else(now_ms);
-    let sequence = messages.last().map(|m| m.sequence + 1).unwrap_or(1);
+    let sequence = next_message_sequence(&tx, &thread.id)?;
+    let id = input.id.unwrap_or_else(|| Uuid::new_v4().to_string());
+    // Without an explicit parent a message continues the branch being shown.
+    let parent_id = match input.parent_id {
+        Some(parent_id) => {
+            if parent_id == id {
+                return Err("A message cannot be its own parent".to_string());
+            }
+            if get_message(&tx, &thread.id, &parent_id)?.is_none() {
+                return Err(format!("Parent message not found in thread: {parent_id}"));
+            }
+            Some(parent_id)
+        }
+        None => match thread.active_leaf_id.clone() {
+            Some(leaf) => Some(leaf),
+            None => last_message_id(&tx, &thread.id)?,
+        },
+    };
+    let parts = input
+        .parts
+        .unwrap_or_else(|| parts_from_content(&input.content));
     let record = MessageRecord {
-        id: input.id.unwrap_or_else(|| Uuid::new_v4().to_string()),
+        id,
         thread_id: thread.id.clone(),
         role: input.role,
         content: input.content,
@@ -37,50 +74,166 @@ pub fn add_message(app: AppHandle, input: MessageCreateInput) -> Result<MessageR
         provider: input.provider,
         mode: input.mode.unwrap_or(AgentMode::Build),
         tokens: input.tokens.unwrap_or_default(),
-        parent_id: input.parent_id,
+        parent_id,
         created_at_ms: ts,
         updated_at_ms: ts,
         sequence,
+        parts,
     };
 
-    messages.push(record.clone());
-    write_json_file(&file, &messages)?;
+    append_message(&tx, &record)?;
 
     thread.updated_at_ms = ts;
-    write_json_file(&thread_file(&root, &thread.id), &thread)?;
+    thread.active_leaf_id = Some(record.id.clone());
+    put_thread(&tx, &thread)?;
+    tx.commit().map_err(|e| e.to_string())?;
 
     Ok(record)
 }
 
 #[tauri::command]
-pub fn list_messages(app: AppHandle, thread_id: String) -> Result<Vec<MessageRecord>, String> {
+pub fn list_messages(
+    app: AppHandle,
+    thread_id: String,
+    before_sequence: Option<u64>,
+    limit: Option<usize>,
+) -> Result<Vec<MessageRecord>, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let conn = open_db(&root)?;
+    let _thread = require_thread(&conn, &thread_id)?;
+
+    if before_sequence.is_none() && limit.is_none() {
+        return list_messages_by_sequence(&conn, &thread_id);
+    }
+    let limit = limit
+        .unwrap_or(MESSAGE_PAGE_SIZE)
+        .clamp(1, MAX_MESSAGE_PAGE_SIZE);
+    list_message_page(&conn, &thread_id, before_sequence, limit)
+}
+
+#[tauri::command]
+pub fn update_message(app: AppHandle, input: MessageUpdateInput) -> Result<MessageRecord, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let _thread_lock = lock_thread(&root, &input.thread_id)?;
+    let conn = open_db(&root)?;
+    let _thread = require_thread(&conn, &input.thread_id)?;
+
+    let mut message = get_message(&conn, &input.thread_id, &input.message_id)?
+        .ok_or_else(|| format!("Message not found: {}", input.message_id))?;
+    if let Some(content) = input.content {
+        message.parts = parts_from_content(&content);
+        message.content = content;
+    }
+    if let Some(parts) = input.parts {
+        message.parts = parts;
+    }
+    if let Some(tokens) = input.tokens {
+        message.tokens = tokens;
+    }
+    if input.model.is_some() {
+        message.model = input.model;
+    }
+    if input.provider.is_some() {
+        message.provider = input.provider;
+    }
+    message.updated_at_ms = now_ms();
+
+    put_message(&conn, &message)?;
+    Ok(message)
+}
+
+#[tauri::command]
+pub fn delete_messages_after(
+    app: AppHandle,
+    thread_id: String,
+    sequence: u64,
+    diffs: Option<LinkedDiffAction>,
+) -> Result<DeletedMessages, String> {
     let root = storage_root(&app)?;
-    ensure_storage_ready(&root)?;
-    let _thread = require_thread(&root, &thread_id)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let _thread_lock = lock_thread(&root, &thread_id)?;
+    let mut conn = open_db(&root)?;
+    let mut thread = require_thread(&conn, &thread_id)?;
+    let action = diffs.unwrap_or_default();
+
+    let dropped = list_messages_by_sequence(&conn, &thread.id)?
+        .into_iter()
+        .filter(|m| m.sequence > sequence)
+        .map(|m| m.id)
+        .collect::<HashSet<_>>();
+    let linked = read_thread_diffs(&root, &thread.id)?
+        .into_iter()
+        .filter(|d| d.message_id.as_ref().is_some_and(|id| dropped.contains(id)))
+        .collect::<Vec<_>>();
 
-    let file = messages_file(&root, &thread_id);
-    if !file.exists() {
-        return Ok(Vec::new());
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let message_ids = delete_message_rows_after(&tx, &thread.id, sequence)?;
+    if thread
+        .active_leaf_id
+        .as_ref()
+        .is_some_and(|id| message_ids.contains(id))
+    {
+        thread.active_leaf_id = last_message_id(&tx, &thread.id)?;
+    }
+    thread.updated_at_ms = now_ms();
+    put_thread(&tx, &thread)?;
+    if action == LinkedDiffAction::Revert {
+        for diff in &linked {
+            unindex_diff(&tx, &diff.id)?;
+        }
+    }
+
+    // The workspace is reverted last, with everything else staged, so a
+    // failed revert leaves the messages in place and the commit follows
+    // straight after a successful one.
+    if action == LinkedDiffAction::Revert && !linked.is_empty() {
+        let project = require_project(&tx, &thread.project_id)?;
+        let workspace_root = canonicalize_workspace(&thread_workspace_path(&project, &thread))?;
+        revert_diffs(&workspace_root, &linked)?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    let mut result = DeletedMessages {
+        thread_id: thread.id.clone(),
+        message_ids,
+        detached_diff_ids: Vec::new(),
+        reverted_diff_ids: Vec::new(),
+    };
+    for mut diff in linked {
+        let path = diff_file(&root, &thread.id, &diff.id);
+        match action {
+            LinkedDiffAction::Detach => {
+                diff.message_id = None;
+                write_json_file(&path, &diff)?;
+                result.detached_diff_ids.push(diff.id);
+            }
+            LinkedDiffAction::Revert => {
+                if path.exists() {
+                    fs::remove_file(&path).map_err(|e| e.to_string())?;
+                }
+                result.reverted_diff_ids.push(diff.id);
+            }
+        }
     }
 
-    let mut messages = read_json_file::<Vec<MessageRecord>>(&file)?;
-    messages.sort_by_key(|m| (m.created_at_ms, m.sequence));
-    Ok(messages)
+    Ok(result)
 }
 
 #[tauri::command]
 pub fn save_diff(app: AppHandle, input: DiffCreateInput) -> Result<DiffRecord, String> {
     let root = storage_root(&app)?;
-    ensure_storage_ready(&root)?;
-    let _thread = require_thread(&root, &input.thread_id)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let _thread_lock = lock_thread(&root, &input.thread_id)?;
+    let conn = open_db(&root)?;
+    let _thread = require_thread(&conn, &input.thread_id)?;
 
     let mut files = Vec::with_capacity(input.files.len());
     for file in input.files {
-        let change_type = match (&file.old_content, &file.new_content) {
-            (None, Some(_)) => FileChangeType::Created,
-            (Some(_), None) => FileChangeType::Deleted,
-            _ => FileChangeType::Modified,
-        };
+        let change_type = classify_change(&file.old_content, &file.new_content);
         files.push(FileSnapshotChange {
             file_path: file.file_path,
             change_type,
@@ -96,28 +249,31 @@ pub fn save_diff(app: AppHandle, input: DiffCreateInput) -> Result<DiffRecord, S
         summary: input.summary,
         created_at_ms: now_ms(),
         files,
+        hunk_reviews: Vec::new(),
     };
 
     write_json_file(&diff_file(&root, &diff.thread_id, &diff.id), &diff)?;
+    index_diff(&conn, &diff)?;
     Ok(diff)
 }
 
 #[tauri::command]
 pub fn list_diffs(app: AppHandle, thread_id: String) -> Result<Vec<DiffRecord>, String> {
     let root = storage_root(&app)?;
-    ensure_storage_ready(&root)?;
-    let _thread = require_thread(&root, &thread_id)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let conn = open_db(&root)?;
+    let _thread = require_thread(&conn, &thread_id)?;
 
-    let mut diffs = read_json_list::<DiffRecord>(&thread_diffs_dir(&root, &thread_id))?;
-    diffs.sort_by_key(|d| d.created_at_ms);
-    Ok(diffs)
+    read_thread_diffs(&root, &thread_id)
 }
 
 #[tauri::command]
 pub fn clear_diffs(app: AppHandle, thread_id: String) -> Result<(), String> {
     let root = storage_root(&app)?;
-    ensure_storage_ready(&root)?;
-    let _thread = require_thread(&root, &thread_id)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let _thread_lock = lock_thread(&root, &thread_id)?;
+    let conn = open_db(&root)?;
+    let _thread = require_thread(&conn, &thread_id)?;
 
     let dir = thread_diffs_dir(&root, &thread_id);
     if !dir.exists() {
@@ -125,5 +281,5 @@ pub fn clear_diffs(app: AppHandle, thread_id: String) -> Result<(), String> {
     }
 
     fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
-    Ok(())
+    unindex_thread_diffs(&conn, &thread_id)
 }
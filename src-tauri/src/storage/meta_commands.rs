@@ -1,16 +1,51 @@
+use std::path::Path;
+use std::time::Duration;
+
 use tauri::AppHandle;
 
-use crate::core::constants::SCHEMA_VERSION;
-use crate::core::models::StorageInfo;
+use crate::core::constants::{SCHEMA_VERSION, TMP_FILE_MIN_AGE_SECS};
+use crate::core::models::{StorageHealth, StorageInfo};
+use crate::storage::db::{list_unreadable_rows, open_db, quarantine_unreadable_rows};
+use crate::storage::health::{cleanup_tmp_files, list_quarantined};
 use crate::storage::io::ensure_storage_ready;
 use crate::storage::paths::storage_root;
 
 #[tauri::command]
 pub fn storage_info(app: AppHandle) -> Result<StorageInfo, String> {
     let root = storage_root(&app)?;
-    ensure_storage_ready(&root)?;
+    let _lock = ensure_storage_ready(&root)?;
     Ok(StorageInfo {
         base_path: root.to_string_lossy().to_string(),
         schema_version: SCHEMA_VERSION,
     })
 }
+
+fn read_health(root: &Path) -> Result<StorageHealth, String> {
+    let (recovered_tmp_files, removed_tmp_files) =
+        cleanup_tmp_files(root, Duration::from_secs(TMP_FILE_MIN_AGE_SECS))?;
+    let unreadable_rows = list_unreadable_rows(&open_db(root)?)?
+        .into_iter()
+        .map(|row| format!("{}/{}: {}", row.table, row.id, row.error))
+        .collect();
+    Ok(StorageHealth {
+        quarantined: list_quarantined(root)?,
+        unreadable_rows,
+        recovered_tmp_files,
+        removed_tmp_files,
+    })
+}
+
+#[tauri::command]
+pub fn storage_health(app: AppHandle) -> Result<StorageHealth, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    read_health(&root)
+}
+
+#[tauri::command]
+pub fn repair_storage(app: AppHandle) -> Result<StorageHealth, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    quarantine_unreadable_rows(&root, &open_db(&root)?)?;
+    read_health(&root)
+}
@@ -0,0 +1,324 @@
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+
+use crate::core::constants::{MANIFEST_FILE, SCHEMA_VERSION};
+use crate::core::models::StorageManifest;
+use crate::core::time::now_ms;
+use crate::core::workspace::normalize_workspace_path;
+use crate::storage::backup::backup_storage;
+use crate::storage::db::{
+    create_schema, list_all_projects, list_all_threads, list_messages_by_sequence, open_db,
+    put_message, put_project,
+};
+use crate::storage::import::import_legacy_json;
+use crate::storage::io::{read_thread_diffs, write_json_file};
+use crate::storage::search::{clear_index, index_diff, index_message, index_thread};
+
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    pub run: fn(&Path) -> Result<(), String>,
+}
+
+// Append new entries and bump `SCHEMA_VERSION`; never edit a shipped migration.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 2,
+        description: "Import per-file JSON projects, threads and messages into SQLite",
+        run: migrate_sqlite_import,
+    },
+    Migration {
+        version: 3,
+        description: "Add archive and trash columns to projects and threads",
+        run: migrate_archive_and_trash,
+    },
+    Migration {
+        version: 4,
+        description: "Build the full-text search index",
+        run: migrate_search_index,
+    },
+    Migration {
+        version: 5,
+        description: "Store structured message parts and reindex message text",
+        run: migrate_message_parts,
+    },
+    Migration {
+        version: 6,
+        description: "Store project workspace paths in canonical form",
+        run: migrate_canonical_workspaces,
+    },
+];
+
+fn migrate_sqlite_import(root: &Path) -> Result<(), String> {
+    let mut conn = open_db(root)?;
+    import_legacy_json(root, &mut conn)
+}
+
+fn migrate_archive_and_trash(root: &Path) -> Result<(), String> {
+    let conn = open_db(root)?;
+    for table in ["projects", "threads"] {
+        add_column_if_missing(&conn, table, "archived", "INTEGER NOT NULL DEFAULT 0")?;
+        add_column_if_missing(&conn, table, "deleted_at_ms", "INTEGER")?;
+    }
+    Ok(())
+}
+
+fn migrate_search_index(root: &Path) -> Result<(), String> {
+    let mut conn = open_db(root)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    clear_index(&tx)?;
+    for thread in list_all_threads(&tx)? {
+        index_thread(&tx, &thread)?;
+        for message in list_messages_by_sequence(&tx, &thread.id)? {
+            index_message(&tx, &message)?;
+        }
+        for diff in read_thread_diffs(root, &thread.id)? {
+            index_diff(&tx, &diff)?;
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+fn migrate_message_parts(root: &Path) -> Result<(), String> {
+    let mut conn = open_db(root)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for thread in list_all_threads(&tx)? {
+        for message in list_messages_by_sequence(&tx, &thread.id)? {
+            put_message(&tx, &message)?;
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+fn migrate_canonical_workspaces(root: &Path) -> Result<(), String> {
+    let mut conn = open_db(root)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for mut project in list_all_projects(&tx)? {
+        let Ok(path) = normalize_workspace_path(&project.workspace_path) else {
+            continue;
+        };
+        if path != project.workspace_path {
+            project.workspace_path = path;
+            put_project(&tx, &project)?;
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), String> {
+    let exists = conn
+        .query_row(
+            &format!("SELECT COUNT(*) FROM pragma_table_info('{table}') WHERE name = ?1"),
+            params![column],
+            |row| row.get::<_, i64>(0),
+        )
+        .map_err(|e| e.to_string())?
+        > 0;
+    if exists {
+        return Ok(());
+    }
+    conn.execute_batch(&format!(
+        "ALTER TABLE {table} ADD COLUMN {column} {definition};"
+    ))
+    .map_err(|e| e.to_string())
+}
+
+pub fn run_migrations(root: &Path, manifest: &mut StorageManifest) -> Result<(), String> {
+    if manifest.schema_version > SCHEMA_VERSION {
+        return Err(format!(
+            "Storage schema version {} is newer than this build supports ({SCHEMA_VERSION}); update Kodit to open it",
+            manifest.schema_version
+        ));
+    }
+
+    let pending = MIGRATIONS
+        .iter()
+        .filter(|m| m.version > manifest.schema_version)
+        .collect::<Vec<_>>();
+    if pending.is_empty() {
+        return Ok(());
+    }
+
+    backup_storage(root, &format!("pre-migration-v{}", manifest.schema_version))?;
+    create_schema(root)?;
+
+    let manifest_path = root.join(MANIFEST_FILE);
+    for migration in pending {
+        (migration.run)(root).map_err(|e| {
+            format!(
+                "Migration to schema version {} ({}) failed: {e}",
+                migration.version, migration.description
+            )
+        })?;
+        manifest.schema_version = migration.version;
+        manifest.updated_at_ms = now_ms();
+        write_json_file(&manifest_path, manifest)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::core::constants::{LEGACY_DIR, PROJECTS_DIR, THREADS_DIR};
+    use crate::core::models::MessagePart;
+    use crate::storage::backup::list_backup_infos;
+    use crate::storage::db::{get_project, get_thread};
+    use crate::storage::health::list_quarantined;
+    use crate::storage::io::{prepare_storage, read_json_file};
+    use crate::storage::paths::messages_file;
+    use crate::storage::search::search;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("kodit-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write(path: &Path, value: serde_json::Value) {
+        write_json_file(path, &value).unwrap();
+    }
+
+    #[test]
+    fn upgrades_legacy_json_storage() {
+        let dir = temp_dir("migrate-legacy");
+        let root = dir.join("storage");
+        let workspace = dir.join("workspace");
+        fs::create_dir_all(&workspace).unwrap();
+        // Stored before paths were canonical.
+        let stored_path = dir.join("workspace/../workspace");
+
+        write(
+            &root.join(PROJECTS_DIR).join("p.json"),
+            json!({
+                "id": "p",
+                "name": "Project",
+                "workspace_path": stored_path.to_string_lossy(),
+                "created_at_ms": 1,
+                "updated_at_ms": 1,
+            }),
+        );
+        write(
+            &root.join(THREADS_DIR).join("t.json"),
+            json!({
+                "id": "t",
+                "project_id": "p",
+                "title": "Thread",
+                "mode": "build",
+                "created_at_ms": 1,
+                "updated_at_ms": 1,
+            }),
+        );
+        let tokens =
+            json!({"input": 0, "output": 0, "reasoning": 0, "cache_read": 0, "cache_write": 0});
+        write(
+            &messages_file(&root, "t"),
+            json!([{
+                "id": "m",
+                "thread_id": "t",
+                "role": "agent",
+                "content": "hello there\n\n[KODIT_META]{\"version\":1,\"reasoning\":\"thinking\"}[/KODIT_META]",
+                "model": null,
+                "provider": null,
+                "mode": "build",
+                "tokens": tokens,
+                "parent_id": null,
+                "created_at_ms": 2,
+                "updated_at_ms": 2,
+                "sequence": 1,
+            }]),
+        );
+
+        prepare_storage(&root).unwrap();
+
+        let manifest = read_json_file::<StorageManifest>(&root.join(MANIFEST_FILE)).unwrap();
+        assert_eq!(manifest.schema_version, SCHEMA_VERSION);
+        assert!(root.join(LEGACY_DIR).join(PROJECTS_DIR).exists());
+        assert!(!root.join(PROJECTS_DIR).exists());
+
+        let conn = open_db(&root).unwrap();
+        let project = get_project(&conn, "p").unwrap().unwrap();
+        assert_eq!(
+            project.workspace_path,
+            normalize_workspace_path(&workspace.to_string_lossy()).unwrap()
+        );
+        let messages = list_messages_by_sequence(&conn, "t").unwrap();
+        assert_eq!(messages.len(), 1);
+        assert!(matches!(
+            messages[0].parts.as_slice(),
+            [MessagePart::Reasoning { .. }, MessagePart::Text { text }] if text == "hello there"
+        ));
+        assert_eq!(search(&conn, "hello", None, 10).unwrap().len(), 1);
+
+        let backups = list_backup_infos(&root).unwrap();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].label, "pre-migration-v1");
+
+        // Already current: nothing runs and no second backup is taken.
+        prepare_storage(&root).unwrap();
+        assert_eq!(list_backup_infos(&root).unwrap().len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn quarantines_unreadable_legacy_messages() {
+        let dir = temp_dir("migrate-corrupt");
+        let root = dir.join("storage");
+        write(
+            &root.join(THREADS_DIR).join("t.json"),
+            json!({
+                "id": "t",
+                "project_id": "p",
+                "title": "Thread",
+                "mode": "build",
+                "created_at_ms": 1,
+                "updated_at_ms": 1,
+            }),
+        );
+        let messages = messages_file(&root, "t");
+        fs::create_dir_all(messages.parent().unwrap()).unwrap();
+        fs::write(&messages, "[{\"id\":").unwrap();
+
+        prepare_storage(&root).unwrap();
+
+        let conn = open_db(&root).unwrap();
+        assert!(get_thread(&conn, "t").unwrap().is_some());
+        assert!(list_messages_by_sequence(&conn, "t").unwrap().is_empty());
+        let quarantined = list_quarantined(&root).unwrap();
+        assert_eq!(quarantined.len(), 1);
+        assert!(quarantined[0].original_path.ends_with("t.json"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn refuses_newer_schema() {
+        let dir = temp_dir("migrate-newer");
+        let mut manifest = StorageManifest {
+            schema_version: SCHEMA_VERSION + 1,
+            created_at_ms: 0,
+            updated_at_ms: 0,
+        };
+        assert!(run_migrations(&dir, &mut manifest).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn versions_are_increasing_and_end_at_current() {
+        assert!(MIGRATIONS.windows(2).all(|w| w[0].version < w[1].version));
+        assert_eq!(MIGRATIONS.last().map(|m| m.version), Some(SCHEMA_VERSION));
+    }
+}
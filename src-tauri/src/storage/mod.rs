@@ -1,5 +1,26 @@
+pub mod archive;
+pub mod archive_commands;
+pub mod backup;
+pub mod backup_commands;
+pub mod branch_commands;
+pub mod db;
+pub mod health;
+pub mod import;
+pub mod integrity;
+pub mod lock;
 pub mod io;
 pub mod message_diff_commands;
 pub mod meta_commands;
+pub mod migrations;
 pub mod paths;
+pub mod project_settings;
 pub mod project_thread_commands;
+pub mod search;
+pub mod search_commands;
+pub mod todo_commands;
+pub mod todos;
+pub mod trash;
+pub mod trash_commands;
+pub mod tree;
+pub mod usage;
+pub mod usage_commands;
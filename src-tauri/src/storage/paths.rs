@@ -2,7 +2,8 @@ use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Manager};
 
 use crate::core::constants::{
-    AUTH_CONFIG_REL_PATH, DIFFS_DIR, MESSAGES_DIR, PROJECTS_DIR, STORAGE_DIR, THREADS_DIR,
+    AUTH_CONFIG_REL_PATH, BACKUPS_DIR, CORRUPT_DIR, DATABASE_FILE, DIFFS_DIR, LOCKS_DIR, MESSAGES_DIR,
+    STORAGE_DIR, STORAGE_LOCK_FILE, WORKTREES_DIR,
 };
 
 pub fn storage_root(app: &AppHandle) -> Result<PathBuf, String> {
@@ -16,12 +17,27 @@ pub fn auth_config_path(app: &AppHandle) -> Result<PathBuf, String> {
     Ok(home_dir.join(AUTH_CONFIG_REL_PATH))
 }
 
-pub fn project_file(root: &Path, id: &str) -> PathBuf {
-    root.join(PROJECTS_DIR).join(format!("{id}.json"))
+pub fn backups_dir(root: &Path) -> Result<PathBuf, String> {
+    let parent = root
+        .parent()
+        .ok_or_else(|| "Storage root has no parent folder".to_string())?;
+    Ok(parent.join(BACKUPS_DIR))
 }
 
-pub fn thread_file(root: &Path, id: &str) -> PathBuf {
-    root.join(THREADS_DIR).join(format!("{id}.json"))
+pub fn corrupt_dir(root: &Path) -> PathBuf {
+    root.join(CORRUPT_DIR)
+}
+
+pub fn storage_lock_file(root: &Path) -> PathBuf {
+    root.join(STORAGE_LOCK_FILE)
+}
+
+pub fn thread_lock_file(root: &Path, thread_id: &str) -> PathBuf {
+    root.join(LOCKS_DIR).join(format!("{thread_id}.lock"))
+}
+
+pub fn database_file(root: &Path) -> PathBuf {
+    root.join(DATABASE_FILE)
 }
 
 pub fn messages_file(root: &Path, thread_id: &str) -> PathBuf {
@@ -35,3 +51,7 @@ pub fn thread_diffs_dir(root: &Path, thread_id: &str) -> PathBuf {
 pub fn diff_file(root: &Path, thread_id: &str, diff_id: &str) -> PathBuf {
     thread_diffs_dir(root, thread_id).join(format!("{diff_id}.json"))
 }
+
+pub fn thread_worktree_dir(root: &Path, thread_id: &str) -> PathBuf {
+    root.join(WORKTREES_DIR).join(thread_id)
+}
@@ -0,0 +1,188 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::core::constants::{DEFAULT_COMMAND_TIMEOUT_MS, PROJECT_CONFIG_DIR, PROJECT_CONFIG_FILE};
+use crate::core::models::{AgentMode, ProjectRecord, ProjectSettings, ProjectSettingsView};
+
+pub fn workspace_config_path(workspace: &Path) -> PathBuf {
+    workspace.join(PROJECT_CONFIG_DIR).join(PROJECT_CONFIG_FILE)
+}
+
+pub fn read_workspace_settings(workspace: &Path) -> Result<Option<ProjectSettings>, String> {
+    let path = workspace_config_path(workspace);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let settings =
+        toml::from_str::<ProjectSettings>(&text).map_err(|e| format!("{}: {e}", path.display()))?;
+    Ok(Some(normalize_settings(settings)?))
+}
+
+pub fn normalize_settings(mut settings: ProjectSettings) -> Result<ProjectSettings, String> {
+    let trimmed = |value: Option<String>| {
+        value
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    settings.model = trimmed(settings.model);
+    settings.provider = trimmed(settings.provider);
+    settings.instructions = trimmed(settings.instructions);
+
+    if settings.command_timeout_ms == Some(0) {
+        return Err("Command timeout must be greater than zero".to_string());
+    }
+    if let Some(key) = settings
+        .env
+        .keys()
+        .find(|k| k.is_empty() || k.contains(['=', '\0']))
+    {
+        return Err(format!("Invalid environment variable name: {key:?}"));
+    }
+
+    let mut protected = Vec::new();
+    for raw in &settings.protected_paths {
+        let path = raw.trim().replace('\\', "/");
+        let path = path.trim_start_matches("./").trim_end_matches('/');
+        if path.is_empty() {
+            continue;
+        }
+        let valid = Path::new(path)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+        if !valid {
+            return Err(format!(
+                "Protected path must be relative to the workspace: {raw}"
+            ));
+        }
+        if !protected.iter().any(|p| p == path) {
+            protected.push(path.to_string());
+        }
+    }
+    settings.protected_paths = protected;
+    Ok(settings)
+}
+
+pub fn merge_settings(
+    workspace: Option<&ProjectSettings>,
+    stored: &ProjectSettings,
+) -> ProjectSettings {
+    let base = workspace.cloned().unwrap_or_default();
+
+    let mut protected_paths = base.protected_paths;
+    for path in &stored.protected_paths {
+        if !protected_paths.contains(path) {
+            protected_paths.push(path.clone());
+        }
+    }
+
+    ProjectSettings {
+        model: stored.model.clone().or(base.model),
+        provider: stored.provider.clone().or(base.provider),
+        mode: stored.mode.or(base.mode).or(Some(AgentMode::Build)),
+        command_timeout_ms: stored
+            .command_timeout_ms
+            .or(base.command_timeout_ms)
+            .or(Some(DEFAULT_COMMAND_TIMEOUT_MS)),
+        protected_paths,
+        // A checked-in file must not set `PATH` or `LD_PRELOAD` for agents.
+        env: stored.env.clone(),
+        instructions: stored.instructions.clone().or(base.instructions),
+    }
+}
+
+pub fn resolve_project_settings(project: &ProjectRecord) -> ProjectSettingsView {
+    let (workspace, workspace_error) =
+        match read_workspace_settings(Path::new(&project.workspace_path)) {
+            Ok(settings) => (settings, None),
+            Err(e) => (None, Some(e)),
+        };
+    let effective = merge_settings(workspace.as_ref(), &project.settings);
+    ProjectSettingsView {
+        stored: project.settings.clone(),
+        workspace,
+        workspace_error,
+        effective,
+    }
+}
+
+pub fn protected_match<'a>(settings: &'a ProjectSettings, relative: &Path) -> Option<&'a str> {
+    settings
+        .protected_paths
+        .iter()
+        .find(|p| relative.starts_with(Path::new(p)))
+        .map(String::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> ProjectSettings {
+        normalize_settings(toml::from_str(text).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn normalizes_protected_paths() {
+        let settings = parse(
+            r#"
+            model = "  "
+            protected_paths = ["./secrets/", "secrets", "config\\prod.toml", ""]
+            "#,
+        );
+        assert_eq!(settings.model, None);
+        assert_eq!(settings.protected_paths, ["secrets", "config/prod.toml"]);
+    }
+
+    #[test]
+    fn rejects_invalid_settings() {
+        for text in [
+            r#"protected_paths = ["../outside"]"#,
+            r#"protected_paths = ["/etc"]"#,
+            "command_timeout_ms = 0",
+            "[env]\n\"A=B\" = \"x\"",
+        ] {
+            let settings = toml::from_str::<ProjectSettings>(text).unwrap();
+            assert!(normalize_settings(settings).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn stored_settings_win() {
+        let workspace = parse(
+            r#"
+            model = "workspace-model"
+            provider = "workspace-provider"
+            protected_paths = ["a"]
+            [env]
+            GIT_PAGER = "sh"
+            "#,
+        );
+        let stored = parse(
+            r#"
+            model = "stored-model"
+            protected_paths = ["b", "a"]
+            [env]
+            RUST_LOG = "debug"
+            "#,
+        );
+        let merged = merge_settings(Some(&workspace), &stored);
+        assert_eq!(merged.model.as_deref(), Some("stored-model"));
+        assert_eq!(merged.provider.as_deref(), Some("workspace-provider"));
+        assert_eq!(merged.protected_paths, ["a", "b"]);
+        assert_eq!(merged.env.len(), 1);
+        assert_eq!(merged.env["RUST_LOG"], "debug");
+        assert_eq!(merged.mode, Some(AgentMode::Build));
+        assert_eq!(merged.command_timeout_ms, Some(DEFAULT_COMMAND_TIMEOUT_MS));
+    }
+
+    #[test]
+    fn matches_protected_prefixes() {
+        let settings = parse(r#"protected_paths = ["secrets"]"#);
+        assert_eq!(
+            protected_match(&settings, Path::new("secrets/key.pem")),
+            Some("secrets")
+        );
+        assert_eq!(protected_match(&settings, Path::new("secrets.txt")), None);
+    }
+}
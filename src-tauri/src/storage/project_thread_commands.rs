@@ -1,68 +1,207 @@
+use std::path::Path;
+
+use rusqlite::{Connection, TransactionBehavior};
 use tauri::AppHandle;
 use uuid::Uuid;
 
-use crate::core::constants::{PROJECTS_DIR, THREADS_DIR};
-use crate::core::models::{AgentMode, ProjectRecord, ProjectUpsertInput, ThreadRecord, ThreadUpsertInput};
-use crate::storage::io::{
-    ensure_storage_ready, read_json_file, read_json_list, require_project, write_json_file,
+use crate::core::models::{
+    AgentMode, ProjectListing, ProjectRecord, ProjectSettings, ProjectSettingsView, ProjectUpsertInput, ThreadRecord,
+    ThreadUpsertInput,
+};
+use crate::core::workspace::normalize_workspace_path;
+use crate::git::runner::run_git;
+use crate::storage::db::{
+    find_projects_by_workspace, get_project, get_thread, list_all_project_threads,
+    list_project_records, list_thread_records, open_db, put_project, put_thread, require_project,
+    require_thread,
 };
-use crate::storage::paths::{project_file, storage_root, thread_file};
+use crate::storage::io::ensure_storage_ready;
+use crate::storage::lock::lock_thread;
+use crate::storage::paths::storage_root;
+use crate::storage::project_settings::{normalize_settings, resolve_project_settings};
 use crate::core::time::now_ms;
+use crate::git::worktree::create_worktree;
+
+fn ensure_workspace_unused(
+    conn: &Connection,
+    workspace_path: &str,
+    project_id: &str,
+) -> Result<(), String> {
+    match find_projects_by_workspace(conn, workspace_path)?
+        .into_iter()
+        .find(|p| p.id != project_id)
+    {
+        Some(other) => Err(format!(
+            "{workspace_path} is already the workspace of project {}",
+            other.name
+        )),
+        None => Ok(()),
+    }
+}
 
 #[tauri::command]
 pub fn upsert_project(app: AppHandle, input: ProjectUpsertInput) -> Result<ProjectRecord, String> {
     let root = storage_root(&app)?;
-    ensure_storage_ready(&root)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let conn = open_db(&root)?;
 
     let id = input.id.unwrap_or_else(|| Uuid::new_v4().to_string());
-    let path = project_file(&root, &id);
     let ts = now_ms();
+    let existing = get_project(&conn, &id)?;
 
-    let record = if path.exists() {
-        let existing = read_json_file::<ProjectRecord>(&path)?;
+    let workspace_path = match &existing {
+        Some(existing) if existing.workspace_path == input.workspace_path => {
+            existing.workspace_path.clone()
+        }
+        _ => {
+            let path = normalize_workspace_path(&input.workspace_path)?;
+            ensure_workspace_unused(&conn, &path, &id)?;
+            path
+        }
+    };
+
+    let record = if let Some(existing) = existing {
         ProjectRecord {
             id,
             name: input.name,
-            workspace_path: input.workspace_path,
+            workspace_path,
             created_at_ms: existing.created_at_ms,
             updated_at_ms: ts,
+            archived: existing.archived,
+            deleted_at_ms: existing.deleted_at_ms,
+            settings: existing.settings,
         }
     } else {
         ProjectRecord {
             id,
             name: input.name,
-            workspace_path: input.workspace_path,
+            workspace_path,
             created_at_ms: ts,
             updated_at_ms: ts,
+            archived: false,
+            deleted_at_ms: None,
+            settings: ProjectSettings::default(),
         }
     };
 
-    write_json_file(&path, &record)?;
+    put_project(&conn, &record)?;
     Ok(record)
 }
 
 #[tauri::command]
-pub fn list_projects(app: AppHandle) -> Result<Vec<ProjectRecord>, String> {
+pub fn relocate_project(
+    app: AppHandle,
+    project_id: String,
+    workspace_path: String,
+) -> Result<ProjectRecord, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let conn = open_db(&root)?;
+    let mut project = require_project(&conn, &project_id)?;
+
+    let workspace_path = normalize_workspace_path(&workspace_path)?;
+    ensure_workspace_unused(&conn, &workspace_path, &project.id)?;
+
+    let worktrees = list_all_project_threads(&conn, &project.id)?
+        .into_iter()
+        .filter_map(|t| t.worktree.map(|w| w.path))
+        .filter(|path| Path::new(path).exists())
+        .collect::<Vec<_>>();
+    if !worktrees.is_empty() {
+        run_git(
+            Path::new(&workspace_path),
+            ["worktree", "repair"]
+                .into_iter()
+                .map(String::from)
+                .chain(worktrees),
+        )?;
+    }
+
+    project.workspace_path = workspace_path;
+    project.updated_at_ms = now_ms();
+    put_project(&conn, &project)?;
+    Ok(project)
+}
+
+#[tauri::command]
+pub fn list_projects(
+    app: AppHandle,
+    archived: Option<bool>,
+) -> Result<Vec<ProjectListing>, String> {
     let root = storage_root(&app)?;
-    ensure_storage_ready(&root)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let conn = open_db(&root)?;
 
-    let mut projects = read_json_list::<ProjectRecord>(&root.join(PROJECTS_DIR))?;
-    projects.sort_by_key(|p| std::cmp::Reverse(p.updated_at_ms));
-    Ok(projects)
+    Ok(list_project_records(&conn, archived.unwrap_or(false))?
+        .into_iter()
+        .map(|project| ProjectListing {
+            workspace_missing: !Path::new(&project.workspace_path).is_dir(),
+            project,
+        })
+        .collect())
 }
 
 #[tauri::command]
-pub fn upsert_thread(app: AppHandle, input: ThreadUpsertInput) -> Result<ThreadRecord, String> {
+pub fn set_project_archived(
+    app: AppHandle,
+    project_id: String,
+    archived: bool,
+) -> Result<ProjectRecord, String> {
     let root = storage_root(&app)?;
-    ensure_storage_ready(&root)?;
-    let _project = require_project(&root, &input.project_id)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let conn = open_db(&root)?;
+    let mut project = require_project(&conn, &project_id)?;
+
+    project.archived = archived;
+    project.updated_at_ms = now_ms();
+    put_project(&conn, &project)?;
+    Ok(project)
+}
 
+#[tauri::command]
+pub fn get_project_settings(
+    app: AppHandle,
+    project_id: String,
+) -> Result<ProjectSettingsView, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let conn = open_db(&root)?;
+    let project = require_project(&conn, &project_id)?;
+    Ok(resolve_project_settings(&project))
+}
+
+#[tauri::command]
+pub fn set_project_settings(
+    app: AppHandle,
+    project_id: String,
+    settings: ProjectSettings,
+) -> Result<ProjectSettingsView, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let conn = open_db(&root)?;
+    let mut project = require_project(&conn, &project_id)?;
+
+    project.settings = normalize_settings(settings)?;
+    project.updated_at_ms = now_ms();
+    put_project(&conn, &project)?;
+    Ok(resolve_project_settings(&project))
+}
+
+#[tauri::command]
+pub fn upsert_thread(app: AppHandle, input: ThreadUpsertInput) -> Result<ThreadRecord, String> {
     let id = input.id.unwrap_or_else(|| Uuid::new_v4().to_string());
-    let path = thread_file(&root, &id);
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let _thread_lock = lock_thread(&root, &id)?;
+    let mut conn = open_db(&root)?;
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let project = require_project(&tx, &input.project_id)?;
     let ts = now_ms();
 
-    let record = if path.exists() {
-        let existing = read_json_file::<ThreadRecord>(&path)?;
+    let mut record = if let Some(existing) = get_thread(&tx, &id)? {
         ThreadRecord {
             id,
             project_id: input.project_id,
@@ -70,32 +209,74 @@ pub fn upsert_thread(app: AppHandle, input: ThreadUpsertInput) -> Result<ThreadR
             mode: input.mode.unwrap_or(existing.mode),
             created_at_ms: existing.created_at_ms,
             updated_at_ms: ts,
+            worktree: existing.worktree,
+            archived: existing.archived,
+            deleted_at_ms: existing.deleted_at_ms,
+            active_leaf_id: existing.active_leaf_id,
+            forked_from: existing.forked_from,
+            todos: existing.todos,
         }
     } else {
         ThreadRecord {
             id,
             project_id: input.project_id,
             title: input.title.unwrap_or_else(|| "New thread".to_string()),
-            mode: input.mode.unwrap_or(AgentMode::Build),
+            mode: input
+                .mode
+                .or(resolve_project_settings(&project).effective.mode)
+                .unwrap_or(AgentMode::Build),
             created_at_ms: ts,
             updated_at_ms: ts,
+            worktree: None,
+            archived: false,
+            deleted_at_ms: None,
+            active_leaf_id: None,
+            forked_from: None,
+            todos: Vec::new(),
         }
     };
 
-    write_json_file(&path, &record)?;
+    if input.worktree.unwrap_or(false) && record.worktree.is_none() {
+        record.worktree = Some(create_worktree(&root, &project, &record.id, None)?);
+    }
+
+    put_thread(&tx, &record)?;
+    tx.commit().map_err(|e| e.to_string())?;
     Ok(record)
 }
 
 #[tauri::command]
-pub fn list_threads(app: AppHandle, project_id: String) -> Result<Vec<ThreadRecord>, String> {
+pub fn list_threads(
+    app: AppHandle,
+    project_id: String,
+    archived: Option<bool>,
+) -> Result<Vec<ThreadRecord>, String> {
     let root = storage_root(&app)?;
-    ensure_storage_ready(&root)?;
-    let _project = require_project(&root, &project_id)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let conn = open_db(&root)?;
+    let _project = require_project(&conn, &project_id)?;
 
-    let mut threads = read_json_list::<ThreadRecord>(&root.join(THREADS_DIR))?
-        .into_iter()
-        .filter(|t| t.project_id == project_id)
-        .collect::<Vec<_>>();
-    threads.sort_by_key(|t| std::cmp::Reverse(t.updated_at_ms));
-    Ok(threads)
+    list_thread_records(&conn, &project_id, archived.unwrap_or(false))
+}
+
+#[tauri::command]
+pub fn set_thread_archived(
+    app: AppHandle,
+    thread_id: String,
+    archived: bool,
+) -> Result<ThreadRecord, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let _thread_lock = lock_thread(&root, &thread_id)?;
+    let mut conn = open_db(&root)?;
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let mut thread = require_thread(&tx, &thread_id)?;
+    thread.archived = archived;
+    thread.updated_at_ms = now_ms();
+    put_thread(&tx, &thread)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(thread)
 }
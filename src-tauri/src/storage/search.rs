@@ -0,0 +1,307 @@
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::core::message_parts::parts_text;
+use crate::core::models::{DiffRecord, MessageRecord, SearchHit, SearchHitKind, ThreadRecord};
+
+pub const SNIPPET_MATCH_START: char = '\u{2}';
+pub const SNIPPET_MATCH_END: char = '\u{3}';
+
+fn kind_key(kind: &SearchHitKind) -> &'static str {
+    match kind {
+        SearchHitKind::Message => "message",
+        SearchHitKind::Thread => "thread",
+        SearchHitKind::Diff => "diff",
+    }
+}
+
+fn kind_from_key(key: &str) -> Option<SearchHitKind> {
+    match key {
+        "message" => Some(SearchHitKind::Message),
+        "thread" => Some(SearchHitKind::Thread),
+        "diff" => Some(SearchHitKind::Diff),
+        _ => None,
+    }
+}
+
+fn remove_document(conn: &Connection, kind: &SearchHitKind, record_id: &str) -> Result<(), String> {
+    let doc_id = conn
+        .query_row(
+            "SELECT id FROM search_docs WHERE kind = ?1 AND record_id = ?2",
+            params![kind_key(kind), record_id],
+            |row| row.get::<_, i64>(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let Some(doc_id) = doc_id else {
+        return Ok(());
+    };
+
+    conn.execute("DELETE FROM search_index WHERE rowid = ?1", params![doc_id])
+        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM search_docs WHERE id = ?1", params![doc_id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn index_document(
+    conn: &Connection,
+    kind: SearchHitKind,
+    record_id: &str,
+    thread_id: &str,
+    body: &str,
+) -> Result<(), String> {
+    remove_document(conn, &kind, record_id)?;
+    if body.trim().is_empty() {
+        return Ok(());
+    }
+
+    conn.execute(
+        "INSERT INTO search_docs (kind, record_id, thread_id) VALUES (?1, ?2, ?3)",
+        params![kind_key(&kind), record_id, thread_id],
+    )
+    .map_err(|e| e.to_string())?;
+    let doc_id = conn.last_insert_rowid();
+    conn.execute(
+        "INSERT INTO search_index (rowid, body) VALUES (?1, ?2)",
+        params![doc_id, body],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn index_message(conn: &Connection, message: &MessageRecord) -> Result<(), String> {
+    index_document(
+        conn,
+        SearchHitKind::Message,
+        &message.id,
+        &message.thread_id,
+        &parts_text(&message.parts),
+    )
+}
+
+pub fn unindex_message(conn: &Connection, message_id: &str) -> Result<(), String> {
+    remove_document(conn, &SearchHitKind::Message, message_id)
+}
+
+pub fn index_thread(conn: &Connection, thread: &ThreadRecord) -> Result<(), String> {
+    index_document(
+        conn,
+        SearchHitKind::Thread,
+        &thread.id,
+        &thread.id,
+        &thread.title,
+    )
+}
+
+pub fn index_diff(conn: &Connection, diff: &DiffRecord) -> Result<(), String> {
+    let mut body = diff
+        .files
+        .iter()
+        .map(|f| f.file_path.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    if let Some(summary) = &diff.summary {
+        body.push('\n');
+        body.push_str(summary);
+    }
+    index_document(conn, SearchHitKind::Diff, &diff.id, &diff.thread_id, &body)
+}
+
+pub fn unindex_diff(conn: &Connection, diff_id: &str) -> Result<(), String> {
+    remove_document(conn, &SearchHitKind::Diff, diff_id)
+}
+
+pub fn unindex_thread_diffs(conn: &Connection, thread_id: &str) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM search_index WHERE rowid IN
+            (SELECT id FROM search_docs WHERE thread_id = ?1 AND kind = 'diff')",
+        params![thread_id],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM search_docs WHERE thread_id = ?1 AND kind = 'diff'",
+        params![thread_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn unindex_thread(conn: &Connection, thread_id: &str) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM search_index WHERE rowid IN
+            (SELECT id FROM search_docs WHERE thread_id = ?1)",
+        params![thread_id],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM search_docs WHERE thread_id = ?1",
+        params![thread_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn clear_index(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch("DELETE FROM search_index; DELETE FROM search_docs;")
+        .map_err(|e| e.to_string())
+}
+
+pub fn fts_query(input: &str) -> Option<String> {
+    let terms = input
+        .split_whitespace()
+        .map(|term| term.replace('"', "\"\""))
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("\"{term}\""))
+        .collect::<Vec<_>>();
+    if terms.is_empty() {
+        return None;
+    }
+    Some(format!("{}*", terms.join(" ")))
+}
+
+pub fn search(
+    conn: &Connection,
+    query: &str,
+    project_id: Option<&str>,
+    limit: usize,
+) -> Result<Vec<SearchHit>, String> {
+    let Some(match_query) = fts_query(query) else {
+        return Ok(Vec::new());
+    };
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT d.kind, d.record_id, d.thread_id, t.project_id,
+                    json_extract(t.data, '$.title'),
+                    snippet(search_index, 0, ?4, ?5, '…', 16),
+                    bm25(search_index)
+             FROM search_index
+             JOIN search_docs d ON d.id = search_index.rowid
+             JOIN threads t ON t.id = d.thread_id
+             JOIN projects p ON p.id = t.project_id
+             WHERE search_index MATCH ?1
+               AND t.deleted_at_ms IS NULL
+               AND p.deleted_at_ms IS NULL
+               AND (?2 IS NULL OR t.project_id = ?2)
+             ORDER BY bm25(search_index)
+             LIMIT ?3",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(
+            params![
+                match_query,
+                project_id,
+                limit as i64,
+                SNIPPET_MATCH_START.to_string(),
+                SNIPPET_MATCH_END.to_string()
+            ],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, String>(5)?,
+                    row.get::<_, f64>(6)?,
+                ))
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut hits = Vec::new();
+    for row in rows {
+        let (kind, record_id, thread_id, project_id, thread_title, snippet, rank) =
+            row.map_err(|e| e.to_string())?;
+        let Some(kind) = kind_from_key(&kind) else {
+            continue;
+        };
+        hits.push(SearchHit {
+            message_id: matches!(kind, SearchHitKind::Message).then(|| record_id.clone()),
+            diff_id: matches!(kind, SearchHitKind::Diff).then(|| record_id.clone()),
+            kind,
+            project_id,
+            thread_id,
+            thread_title: thread_title.unwrap_or_default(),
+            snippet,
+            score: -rank,
+        });
+    }
+    Ok(hits)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::core::models::{AgentMode, ProjectRecord, ProjectSettings};
+    use crate::storage::db::{open_db, put_project, put_thread};
+    use crate::storage::io::prepare_storage;
+
+    #[test]
+    fn quotes_terms_and_prefixes_the_last_one() {
+        assert_eq!(fts_query("  "), None);
+        assert_eq!(fts_query("hello"), Some("\"hello\"*".to_string()));
+        assert_eq!(
+            fts_query("fix  parser"),
+            Some("\"fix\" \"parser\"*".to_string())
+        );
+        assert_eq!(
+            fts_query("say \"hi\" OR NEAR("),
+            Some("\"say\" \"\"\"hi\"\"\" \"OR\" \"NEAR(\"*".to_string())
+        );
+    }
+
+    #[test]
+    fn operators_and_quotes_are_searched_as_text() {
+        let dir = std::env::temp_dir().join(format!("kodit-search-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let root = dir.join("storage");
+        prepare_storage(&root).unwrap();
+        let conn = open_db(&root).unwrap();
+        let thread = ThreadRecord {
+            id: "t".to_string(),
+            project_id: "p".to_string(),
+            title: "Parser cleanup".to_string(),
+            mode: AgentMode::Build,
+            created_at_ms: 0,
+            updated_at_ms: 0,
+            worktree: None,
+            archived: false,
+            deleted_at_ms: None,
+            active_leaf_id: None,
+            forked_from: None,
+            todos: Vec::new(),
+        };
+        put_project(
+            &conn,
+            &ProjectRecord {
+                id: "p".to_string(),
+                name: "p".to_string(),
+                workspace_path: "/p".to_string(),
+                created_at_ms: 0,
+                updated_at_ms: 0,
+                archived: false,
+                deleted_at_ms: None,
+                settings: ProjectSettings::default(),
+            },
+        )
+        .unwrap();
+        put_thread(&conn, &thread).unwrap();
+
+        let hits = search(&conn, "pars", None, 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].thread_id, "t");
+        assert!(search(&conn, "cleanup parsers", None, 10)
+            .unwrap()
+            .is_empty());
+        for query in ["\"parser", "parser OR", "NEAR(parser", "-parser", "*"] {
+            assert!(search(&conn, query, None, 10).is_ok(), "{query}");
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,26 @@
+use tauri::AppHandle;
+
+use crate::core::constants::SEARCH_RESULT_LIMIT;
+use crate::core::models::SearchHit;
+use crate::storage::db::{open_db, require_project};
+use crate::storage::io::ensure_storage_ready;
+use crate::storage::paths::storage_root;
+use crate::storage::search::search;
+
+#[tauri::command]
+pub fn search_history(
+    app: AppHandle,
+    query: String,
+    project_id: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<SearchHit>, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let conn = open_db(&root)?;
+    if let Some(project_id) = &project_id {
+        let _project = require_project(&conn, project_id)?;
+    }
+
+    let limit = limit.unwrap_or(SEARCH_RESULT_LIMIT).clamp(1, 500);
+    search(&conn, &query, project_id.as_deref(), limit)
+}
@@ -0,0 +1,56 @@
+use rusqlite::TransactionBehavior;
+use tauri::AppHandle;
+
+use crate::core::models::{TodoEvent, TodoItem};
+use crate::core::time::now_ms;
+use crate::storage::db::{open_db, put_thread, require_thread};
+use crate::storage::io::ensure_storage_ready;
+use crate::storage::lock::lock_thread;
+use crate::storage::paths::storage_root;
+use crate::storage::todos::{append_todo_events, diff_todos, list_todo_events, validate_todos};
+
+#[tauri::command]
+pub fn read_todos(app: AppHandle, thread_id: String) -> Result<Vec<TodoItem>, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let conn = open_db(&root)?;
+    Ok(require_thread(&conn, &thread_id)?.todos)
+}
+
+#[tauri::command]
+pub fn write_todos(
+    app: AppHandle,
+    thread_id: String,
+    todos: Vec<TodoItem>,
+) -> Result<Vec<TodoItem>, String> {
+    validate_todos(&todos)?;
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let _thread_lock = lock_thread(&root, &thread_id)?;
+    let mut conn = open_db(&root)?;
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let mut thread = require_thread(&tx, &thread_id)?;
+    let events = diff_todos(&thread.todos, &todos, now_ms());
+    if events.is_empty() && thread.todos == todos {
+        return Ok(todos);
+    }
+
+    append_todo_events(&tx, &thread.id, &events)?;
+    thread.todos = todos;
+    put_thread(&tx, &thread)?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(thread.todos)
+}
+
+#[tauri::command]
+pub fn list_todo_history(app: AppHandle, thread_id: String) -> Result<Vec<TodoEvent>, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let conn = open_db(&root)?;
+    let thread = require_thread(&conn, &thread_id)?;
+    list_todo_events(&conn, &thread.id)
+}
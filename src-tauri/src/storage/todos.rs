@@ -0,0 +1,178 @@
+use std::collections::{HashMap, HashSet};
+
+use rusqlite::{params, Connection};
+
+use crate::core::models::{TodoChange, TodoEvent, TodoItem};
+
+pub fn validate_todos(todos: &[TodoItem]) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    for todo in todos {
+        if todo.id.trim().is_empty() {
+            return Err("Todo id cannot be empty".to_string());
+        }
+        if todo.content.trim().is_empty() {
+            return Err(format!("Todo {} has no content", todo.id));
+        }
+        if !seen.insert(todo.id.as_str()) {
+            return Err(format!("Duplicate todo id: {}", todo.id));
+        }
+    }
+    Ok(())
+}
+
+pub fn diff_todos(previous: &[TodoItem], next: &[TodoItem], at_ms: u64) -> Vec<TodoEvent> {
+    let before = previous
+        .iter()
+        .map(|t| (t.id.as_str(), t))
+        .collect::<HashMap<_, _>>();
+    let after = next.iter().map(|t| t.id.as_str()).collect::<HashSet<_>>();
+
+    let mut events = Vec::new();
+    for todo in next {
+        let (change, previous_status) = match before.get(todo.id.as_str()) {
+            None => (TodoChange::Added, None),
+            Some(old) if old.status != todo.status => (TodoChange::StatusChanged, Some(old.status)),
+            Some(old) if *old != todo => (TodoChange::Edited, Some(old.status)),
+            Some(_) => continue,
+        };
+        events.push(TodoEvent {
+            todo_id: todo.id.clone(),
+            change,
+            content: todo.content.clone(),
+            status: todo.status,
+            previous_status,
+            at_ms,
+        });
+    }
+    for todo in previous.iter().filter(|t| !after.contains(t.id.as_str())) {
+        events.push(TodoEvent {
+            todo_id: todo.id.clone(),
+            change: TodoChange::Removed,
+            content: todo.content.clone(),
+            status: todo.status,
+            previous_status: Some(todo.status),
+            at_ms,
+        });
+    }
+    events
+}
+
+pub fn append_todo_events(
+    conn: &Connection,
+    thread_id: &str,
+    events: &[TodoEvent],
+) -> Result<(), String> {
+    for event in events {
+        let data = serde_json::to_string(event).map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO todo_events (thread_id, at_ms, data) VALUES (?1, ?2, ?3)",
+            params![thread_id, event.at_ms as i64, data],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+pub fn list_todo_events(conn: &Connection, thread_id: &str) -> Result<Vec<TodoEvent>, String> {
+    let mut stmt = conn
+        .prepare("SELECT data FROM todo_events WHERE thread_id = ?1 ORDER BY id")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![thread_id], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?;
+
+    let mut out = Vec::new();
+    for row in rows {
+        let data = row.map_err(|e| e.to_string())?;
+        out.push(serde_json::from_str(&data).map_err(|e| e.to_string())?);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{TodoPriority, TodoStatus};
+
+    fn todo(id: &str, content: &str, status: TodoStatus) -> TodoItem {
+        TodoItem {
+            id: id.to_string(),
+            content: content.to_string(),
+            status,
+            priority: None,
+        }
+    }
+
+    #[test]
+    fn records_each_kind_of_change() {
+        let previous = vec![
+            todo("same", "Unchanged", TodoStatus::Pending),
+            todo("status", "Run tests", TodoStatus::Pending),
+            todo("edit", "Write docs", TodoStatus::InProgress),
+            todo("gone", "Old idea", TodoStatus::Pending),
+        ];
+        let mut edited = todo("edit", "Write the docs", TodoStatus::InProgress);
+        edited.priority = Some(TodoPriority::High);
+        let next = vec![
+            todo("same", "Unchanged", TodoStatus::Pending),
+            todo("status", "Run tests", TodoStatus::Completed),
+            edited,
+            todo("new", "Ship it", TodoStatus::Pending),
+        ];
+
+        let events = diff_todos(&previous, &next, 42);
+        let summary = events
+            .iter()
+            .map(|e| {
+                (
+                    e.todo_id.as_str(),
+                    e.change,
+                    e.content.as_str(),
+                    e.status,
+                    e.previous_status,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                (
+                    "status",
+                    TodoChange::StatusChanged,
+                    "Run tests",
+                    TodoStatus::Completed,
+                    Some(TodoStatus::Pending)
+                ),
+                (
+                    "edit",
+                    TodoChange::Edited,
+                    "Write the docs",
+                    TodoStatus::InProgress,
+                    Some(TodoStatus::InProgress)
+                ),
+                (
+                    "new",
+                    TodoChange::Added,
+                    "Ship it",
+                    TodoStatus::Pending,
+                    None
+                ),
+                (
+                    "gone",
+                    TodoChange::Removed,
+                    "Old idea",
+                    TodoStatus::Pending,
+                    Some(TodoStatus::Pending)
+                ),
+            ]
+        );
+        assert!(events.iter().all(|e| e.at_ms == 42));
+    }
+
+    #[test]
+    fn unchanged_lists_record_nothing() {
+        let todos = vec![todo("a", "One", TodoStatus::Pending)];
+        assert!(diff_todos(&todos, &todos, 0).is_empty());
+        assert!(diff_todos(&[], &[], 0).is_empty());
+    }
+}
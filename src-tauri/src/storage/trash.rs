@@ -0,0 +1,227 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use rusqlite::{Connection, TransactionBehavior};
+
+use crate::core::constants::TRASH_RETENTION_MS;
+use crate::core::models::{ProjectRecord, ThreadRecord};
+use crate::core::time::now_ms;
+use crate::git::worktree::remove_worktree;
+use crate::storage::db::{
+    delete_project_row, delete_thread_rows, get_project, get_thread, list_all_project_threads,
+    list_trashed_projects, list_trashed_threads, open_db,
+};
+use crate::storage::io::ensure_storage_ready;
+use crate::storage::lock::lock_thread;
+use crate::storage::paths::thread_diffs_dir;
+
+pub struct PurgedThread {
+    thread: ThreadRecord,
+    project: Option<ProjectRecord>,
+}
+
+pub fn trash_expired(deleted_at_ms: Option<u64>, now: u64) -> bool {
+    deleted_at_ms.is_some_and(|ts| ts.saturating_add(TRASH_RETENTION_MS) <= now)
+}
+
+pub fn purge_thread(conn: &Connection, thread: ThreadRecord) -> Result<PurgedThread, String> {
+    let project = get_project(conn, &thread.project_id)?;
+    delete_thread_rows(conn, &thread.id)?;
+    Ok(PurgedThread { thread, project })
+}
+
+pub fn purge_project(
+    conn: &Connection,
+    project: &ProjectRecord,
+) -> Result<Vec<PurgedThread>, String> {
+    let purged = list_all_project_threads(conn, &project.id)?
+        .into_iter()
+        .map(|thread| purge_thread(conn, thread))
+        .collect::<Result<Vec<_>, _>>()?;
+    delete_project_row(conn, &project.id)?;
+    Ok(purged)
+}
+
+// Only called once the rows are committed, so a failed purge never loses files.
+pub fn remove_purged_files(root: &Path, purged: &[PurgedThread]) -> Result<(), String> {
+    let mut first_error = None;
+    for PurgedThread { thread, project } in purged {
+        if let (Some(worktree), Some(project)) = (&thread.worktree, project) {
+            // Unmerged work on the branch goes with the thread.
+            if let Err(error) = remove_worktree(project, worktree, true) {
+                first_error.get_or_insert(error);
+            }
+        }
+        let diffs = thread_diffs_dir(root, &thread.id);
+        if diffs.exists() {
+            if let Err(error) = fs::remove_dir_all(&diffs) {
+                first_error.get_or_insert(error.to_string());
+            }
+        }
+    }
+    first_error.map_or(Ok(()), Err)
+}
+
+pub fn purge_trash(
+    root: &Path,
+    conn: &mut Connection,
+    projects: &[ProjectRecord],
+    threads: &[ThreadRecord],
+) -> Result<usize, String> {
+    let mut thread_ids = threads
+        .iter()
+        .map(|t| t.id.clone())
+        .collect::<BTreeSet<_>>();
+    for project in projects {
+        thread_ids.extend(
+            list_all_project_threads(conn, &project.id)?
+                .into_iter()
+                .map(|t| t.id),
+        );
+    }
+    let _thread_locks = thread_ids
+        .iter()
+        .map(|id| lock_thread(root, id))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let mut purged = Vec::new();
+    for project in projects {
+        purged.extend(purge_project(&tx, project)?);
+    }
+    for thread in threads {
+        // Threads of a purged project went with it.
+        if let Some(thread) = get_thread(&tx, &thread.id)? {
+            purged.push(purge_thread(&tx, thread)?);
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    remove_purged_files(root, &purged)?;
+    Ok(projects.len() + threads.len())
+}
+
+pub fn purge_expired_trash(root: &Path) -> Result<usize, String> {
+    let _lock = ensure_storage_ready(root)?;
+    let mut conn = open_db(root)?;
+    let now = now_ms();
+
+    let projects = list_trashed_projects(&conn)?
+        .into_iter()
+        .filter(|p| trash_expired(p.deleted_at_ms, now))
+        .collect::<Vec<_>>();
+    let threads = list_trashed_threads(&conn)?
+        .into_iter()
+        .filter(|t| trash_expired(t.deleted_at_ms, now))
+        .collect::<Vec<_>>();
+    purge_trash(root, &mut conn, &projects, &threads)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::core::models::{AgentMode, ProjectSettings};
+    use crate::storage::db::{put_project, put_thread};
+    use crate::storage::io::prepare_storage;
+
+    fn setup(name: &str) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("kodit-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let root = dir.join("storage");
+        prepare_storage(&root).unwrap();
+        (dir, root)
+    }
+
+    fn project(conn: &Connection, id: &str, deleted_at_ms: Option<u64>) -> ProjectRecord {
+        let project = ProjectRecord {
+            id: id.to_string(),
+            name: id.to_string(),
+            workspace_path: format!("/{id}"),
+            created_at_ms: 0,
+            updated_at_ms: 0,
+            archived: false,
+            deleted_at_ms,
+            settings: ProjectSettings::default(),
+        };
+        put_project(conn, &project).unwrap();
+        project
+    }
+
+    fn thread(
+        root: &Path,
+        conn: &Connection,
+        id: &str,
+        project_id: &str,
+        deleted_at_ms: Option<u64>,
+    ) -> ThreadRecord {
+        let thread = ThreadRecord {
+            id: id.to_string(),
+            project_id: project_id.to_string(),
+            title: id.to_string(),
+            mode: AgentMode::Build,
+            created_at_ms: 0,
+            updated_at_ms: 0,
+            worktree: None,
+            archived: false,
+            deleted_at_ms,
+            active_leaf_id: None,
+            forked_from: None,
+            todos: Vec::new(),
+        };
+        put_thread(conn, &thread).unwrap();
+        let diffs = thread_diffs_dir(root, id);
+        fs::create_dir_all(&diffs).unwrap();
+        fs::write(diffs.join("d.json"), "{}").unwrap();
+        thread
+    }
+
+    #[test]
+    fn trash_expires_after_the_retention_window() {
+        assert!(!trash_expired(None, u64::MAX));
+        assert!(!trash_expired(Some(10), 10 + TRASH_RETENTION_MS - 1));
+        assert!(trash_expired(Some(10), 10 + TRASH_RETENTION_MS));
+        assert!(!trash_expired(Some(u64::MAX), u64::MAX - 1));
+    }
+
+    #[test]
+    fn purging_a_project_takes_its_threads_and_files() {
+        let (dir, root) = setup("trash-cascade");
+        let mut conn = open_db(&root).unwrap();
+        let trashed = project(&conn, "p", Some(1));
+        thread(&root, &conn, "t1", "p", None);
+        project(&conn, "q", None);
+        let trashed_thread = thread(&root, &conn, "t2", "q", Some(1));
+        thread(&root, &conn, "t3", "q", None);
+
+        let purged = purge_trash(&root, &mut conn, &[trashed], &[trashed_thread]).unwrap();
+
+        assert_eq!(purged, 2);
+        assert!(get_project(&conn, "p").unwrap().is_none());
+        assert!(get_project(&conn, "q").unwrap().is_some());
+        for (id, kept) in [("t1", false), ("t2", false), ("t3", true)] {
+            assert_eq!(get_thread(&conn, id).unwrap().is_some(), kept);
+            assert_eq!(thread_diffs_dir(&root, id).exists(), kept);
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn expired_purge_keeps_recent_trash() {
+        let (dir, root) = setup("trash-expired");
+        let conn = open_db(&root).unwrap();
+        project(&conn, "p", None);
+        thread(&root, &conn, "old", "p", Some(1));
+        thread(&root, &conn, "recent", "p", Some(now_ms()));
+
+        assert_eq!(purge_expired_trash(&root).unwrap(), 1);
+        assert!(get_thread(&conn, "old").unwrap().is_none());
+        assert!(get_thread(&conn, "recent").unwrap().is_some());
+        assert!(thread_diffs_dir(&root, "recent").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,136 @@
+use rusqlite::TransactionBehavior;
+use tauri::AppHandle;
+
+use crate::core::constants::TRASH_RETENTION_MS;
+use crate::core::models::{ProjectRecord, ThreadRecord, TrashListing};
+use crate::core::time::now_ms;
+use crate::storage::db::{
+    get_project, get_thread, list_trashed_projects, list_trashed_threads, open_db, put_project,
+    put_thread, require_project,
+};
+use crate::storage::io::ensure_storage_ready;
+use crate::storage::lock::lock_thread;
+use crate::storage::paths::storage_root;
+use crate::storage::trash::{purge_thread, purge_trash, remove_purged_files, trash_expired};
+
+#[tauri::command]
+pub fn delete_project(
+    app: AppHandle,
+    project_id: String,
+    permanent: Option<bool>,
+) -> Result<(), String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let mut conn = open_db(&root)?;
+    let mut project = get_project(&conn, &project_id)?
+        .ok_or_else(|| format!("Project not found: {project_id}"))?;
+
+    if permanent.unwrap_or(false) {
+        return purge_trash(&root, &mut conn, &[project], &[]).map(|_| ());
+    }
+    if project.deleted_at_ms.is_none() {
+        project.deleted_at_ms = Some(now_ms());
+        put_project(&conn, &project)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_thread(
+    app: AppHandle,
+    thread_id: String,
+    permanent: Option<bool>,
+) -> Result<(), String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let _thread_lock = lock_thread(&root, &thread_id)?;
+    let mut conn = open_db(&root)?;
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let mut thread =
+        get_thread(&tx, &thread_id)?.ok_or_else(|| format!("Thread not found: {thread_id}"))?;
+
+    if permanent.unwrap_or(false) {
+        let purged = purge_thread(&tx, thread)?;
+        tx.commit().map_err(|e| e.to_string())?;
+        return remove_purged_files(&root, &[purged]);
+    }
+    if thread.deleted_at_ms.is_none() {
+        thread.deleted_at_ms = Some(now_ms());
+        put_thread(&tx, &thread)?;
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_trash(app: AppHandle) -> Result<TrashListing, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let conn = open_db(&root)?;
+
+    Ok(TrashListing {
+        projects: list_trashed_projects(&conn)?,
+        threads: list_trashed_threads(&conn)?,
+        retention_ms: TRASH_RETENTION_MS,
+    })
+}
+
+#[tauri::command]
+pub fn restore_project(app: AppHandle, project_id: String) -> Result<ProjectRecord, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let conn = open_db(&root)?;
+    let mut project = get_project(&conn, &project_id)?
+        .filter(|p| p.deleted_at_ms.is_some())
+        .ok_or_else(|| format!("Project is not in the trash: {project_id}"))?;
+    if trash_expired(project.deleted_at_ms, now_ms()) {
+        return Err(format!(
+            "Project is past the trash retention window: {project_id}"
+        ));
+    }
+
+    project.deleted_at_ms = None;
+    project.updated_at_ms = now_ms();
+    put_project(&conn, &project)?;
+    Ok(project)
+}
+
+#[tauri::command]
+pub fn restore_thread(app: AppHandle, thread_id: String) -> Result<ThreadRecord, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let _thread_lock = lock_thread(&root, &thread_id)?;
+    let mut conn = open_db(&root)?;
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let mut thread = get_thread(&tx, &thread_id)?
+        .filter(|t| t.deleted_at_ms.is_some())
+        .ok_or_else(|| format!("Thread is not in the trash: {thread_id}"))?;
+    if trash_expired(thread.deleted_at_ms, now_ms()) {
+        return Err(format!(
+            "Thread is past the trash retention window: {thread_id}"
+        ));
+    }
+    let _project = require_project(&tx, &thread.project_id)?;
+
+    thread.deleted_at_ms = None;
+    thread.updated_at_ms = now_ms();
+    put_thread(&tx, &thread)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(thread)
+}
+
+#[tauri::command]
+pub fn empty_trash(app: AppHandle) -> Result<usize, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let mut conn = open_db(&root)?;
+
+    let projects = list_trashed_projects(&conn)?;
+    let threads = list_trashed_threads(&conn)?;
+    purge_trash(&root, &mut conn, &projects, &threads)
+}
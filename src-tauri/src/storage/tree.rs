@@ -0,0 +1,163 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::core::models::MessageRecord;
+
+pub struct MessageTree<'a> {
+    by_id: HashMap<&'a str, &'a MessageRecord>,
+    parents: HashMap<&'a str, Option<&'a str>>,
+    children: HashMap<Option<&'a str>, Vec<&'a str>>,
+    last_id: Option<&'a str>,
+}
+
+impl<'a> MessageTree<'a> {
+    // `messages` must be sorted by sequence.
+    pub fn new(messages: &'a [MessageRecord]) -> Self {
+        let by_id = messages
+            .iter()
+            .map(|m| (m.id.as_str(), m))
+            .collect::<HashMap<_, _>>();
+
+        let mut parents = HashMap::new();
+        let mut children: HashMap<Option<&str>, Vec<&str>> = HashMap::new();
+        let mut previous: Option<&str> = None;
+        for message in messages {
+            let parent = match message.parent_id.as_deref() {
+                Some(parent) if by_id.contains_key(parent) => Some(parent),
+                _ => previous,
+            };
+            parents.insert(message.id.as_str(), parent);
+            children
+                .entry(parent)
+                .or_default()
+                .push(message.id.as_str());
+            previous = Some(message.id.as_str());
+        }
+
+        Self {
+            by_id,
+            parents,
+            children,
+            last_id: messages.last().map(|m| m.id.as_str()),
+        }
+    }
+
+    pub fn contains(&self, message_id: &str) -> bool {
+        self.by_id.contains_key(message_id)
+    }
+
+    pub fn get(&self, message_id: &str) -> Option<&'a MessageRecord> {
+        self.by_id.get(message_id).copied()
+    }
+
+    pub fn parent_of(&self, message_id: &str) -> Option<&'a str> {
+        self.parents.get(message_id).copied().flatten()
+    }
+
+    pub fn siblings_of(&self, message_id: &str) -> Vec<&'a str> {
+        self.children
+            .get(&self.parent_of(message_id))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn latest_leaf_under(&self, message_id: &'a str) -> &'a str {
+        let mut current = message_id;
+        let mut seen = HashSet::from([current]);
+        while let Some(last_child) = self
+            .children
+            .get(&Some(current))
+            .and_then(|c| c.last().copied())
+        {
+            // Stored parent links can loop; stop rather than walk forever.
+            if !seen.insert(last_child) {
+                break;
+            }
+            current = last_child;
+        }
+        current
+    }
+
+    pub fn active_leaf(&self, preferred: Option<&str>) -> Option<&'a str> {
+        preferred
+            .and_then(|id| self.by_id.get_key_value(id).map(|(k, _)| *k))
+            .map(|id| self.latest_leaf_under(id))
+            .or(self.last_id)
+    }
+
+    pub fn path_to(&self, message_id: &str) -> Vec<&'a MessageRecord> {
+        let mut path = Vec::new();
+        let mut seen = HashSet::new();
+        let mut current = self.by_id.get_key_value(message_id).map(|(k, _)| *k);
+        while let Some(id) = current.filter(|id| seen.insert(*id)) {
+            path.push(self.by_id[id]);
+            current = self.parent_of(id);
+        }
+        path.reverse();
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{AgentMode, MessageRole};
+
+    fn message(id: &str, parent: Option<&str>, sequence: u64) -> MessageRecord {
+        MessageRecord {
+            id: id.to_string(),
+            thread_id: "t".to_string(),
+            role: MessageRole::User,
+            content: String::new(),
+            model: None,
+            provider: None,
+            mode: AgentMode::Build,
+            tokens: Default::default(),
+            parent_id: parent.map(str::to_string),
+            created_at_ms: sequence,
+            updated_at_ms: sequence,
+            sequence,
+            parts: Vec::new(),
+        }
+    }
+
+    fn ids(path: Vec<&MessageRecord>) -> Vec<&str> {
+        path.into_iter().map(|m| m.id.as_str()).collect()
+    }
+
+    #[test]
+    fn legacy_messages_chain_by_sequence() {
+        let messages = vec![message("a", None, 1), message("b", None, 2)];
+        let tree = MessageTree::new(&messages);
+        assert_eq!(tree.parent_of("b"), Some("a"));
+        assert_eq!(ids(tree.path_to("b")), ["a", "b"]);
+    }
+
+    #[test]
+    fn follows_branches() {
+        let messages = vec![
+            message("a", None, 1),
+            message("b", Some("a"), 2),
+            message("c", Some("a"), 3),
+            message("d", Some("b"), 4),
+        ];
+        let tree = MessageTree::new(&messages);
+        assert_eq!(tree.siblings_of("b"), ["b", "c"]);
+        assert_eq!(tree.latest_leaf_under("b"), "d");
+        assert_eq!(tree.active_leaf(Some("c")), Some("c"));
+        assert_eq!(tree.active_leaf(Some("gone")), Some("d"));
+        assert_eq!(ids(tree.path_to("d")), ["a", "b", "d"]);
+    }
+
+    #[test]
+    fn cycles_terminate() {
+        let messages = vec![message("a", Some("b"), 1), message("b", Some("a"), 2)];
+        let tree = MessageTree::new(&messages);
+        assert_eq!(tree.path_to("a").len(), 2);
+        assert!(["a", "b"].contains(&tree.latest_leaf_under("a")));
+
+        let messages = vec![message("a", Some("a"), 1)];
+        let tree = MessageTree::new(&messages);
+        assert_eq!(ids(tree.path_to("a")), ["a"]);
+        assert_eq!(tree.latest_leaf_under("a"), "a");
+    }
+}
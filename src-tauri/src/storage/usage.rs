@@ -0,0 +1,474 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+
+use crate::core::constants::{UNKNOWN_USAGE_KEY, USAGE_SETTINGS_FILE};
+use crate::core::models::{
+    BudgetAction, BudgetPeriod, BudgetScope, BudgetStatus, ModelPrice, TokenUsage, UsageBudget,
+    UsageGroup, UsageGroupBy, UsageReport, UsageSettings, UsageTotals,
+};
+use crate::core::time::{now_ms, start_of_utc_day, start_of_utc_month, utc_date};
+use crate::storage::io::{read_json_file, write_json_file};
+
+pub struct UsageRow {
+    pub project_id: String,
+    pub thread_id: String,
+    pub model: Option<String>,
+    pub provider: Option<String>,
+    pub day_start_ms: u64,
+    pub tokens: TokenUsage,
+    pub message_count: u64,
+}
+
+#[derive(Default)]
+pub struct UsageFilter<'a> {
+    pub project_id: Option<&'a str>,
+    pub thread_id: Option<&'a str>,
+    pub since_ms: Option<u64>,
+    pub until_ms: Option<u64>,
+}
+
+pub fn read_usage_settings(root: &Path) -> Result<UsageSettings, String> {
+    let path = root.join(USAGE_SETTINGS_FILE);
+    if !path.exists() {
+        return Ok(UsageSettings::default());
+    }
+    read_json_file(&path)
+}
+
+pub fn write_usage_settings(root: &Path, settings: &UsageSettings) -> Result<(), String> {
+    write_json_file(&root.join(USAGE_SETTINGS_FILE), settings)
+}
+
+pub fn query_usage(conn: &Connection, filter: &UsageFilter) -> Result<Vec<UsageRow>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.project_id, m.thread_id,
+                json_extract(m.data, '$.model'), json_extract(m.data, '$.provider'),
+                (m.created_at_ms / 86400000) * 86400000 AS day,
+                SUM(json_extract(m.data, '$.tokens.input')),
+                SUM(json_extract(m.data, '$.tokens.output')),
+                SUM(json_extract(m.data, '$.tokens.reasoning')),
+                SUM(json_extract(m.data, '$.tokens.cache_read')),
+                SUM(json_extract(m.data, '$.tokens.cache_write')),
+                COUNT(*)
+             FROM messages m JOIN threads t ON t.id = m.thread_id
+             WHERE (?1 IS NULL OR t.project_id = ?1)
+               AND (?2 IS NULL OR m.thread_id = ?2)
+               AND m.created_at_ms >= ?3 AND m.created_at_ms < ?4
+             GROUP BY 1, 2, 3, 4, 5",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(
+            params![
+                filter.project_id,
+                filter.thread_id,
+                filter.since_ms.unwrap_or(0) as i64,
+                filter.until_ms.map(|ms| ms as i64).unwrap_or(i64::MAX)
+            ],
+            |row| {
+                let count = |index: usize| {
+                    row.get::<_, Option<i64>>(index)
+                        .map(|v| v.unwrap_or(0) as u64)
+                };
+                Ok(UsageRow {
+                    project_id: row.get(0)?,
+                    thread_id: row.get(1)?,
+                    model: row.get(2)?,
+                    provider: row.get(3)?,
+                    day_start_ms: row.get::<_, i64>(4)? as u64,
+                    tokens: TokenUsage {
+                        input: count(5)?,
+                        output: count(6)?,
+                        reasoning: count(7)?,
+                        cache_read: count(8)?,
+                        cache_write: count(9)?,
+                    },
+                    message_count: count(10)?,
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+fn find_price<'a>(prices: &'a [ModelPrice], row: &UsageRow) -> Option<&'a ModelPrice> {
+    let model = row.model.as_deref()?;
+    prices.iter().find(|price| {
+        price.model == model
+            && price
+                .provider
+                .as_deref()
+                .is_none_or(|provider| row.provider.as_deref() == Some(provider))
+    })
+}
+
+fn has_tokens(tokens: &TokenUsage) -> bool {
+    tokens.input + tokens.output + tokens.reasoning + tokens.cache_read + tokens.cache_write > 0
+}
+
+fn add_row(totals: &mut UsageTotals, row: &UsageRow, prices: &[ModelPrice]) {
+    let tokens = &row.tokens;
+    totals.tokens.input += tokens.input;
+    totals.tokens.output += tokens.output;
+    totals.tokens.reasoning += tokens.reasoning;
+    totals.tokens.cache_read += tokens.cache_read;
+    totals.tokens.cache_write += tokens.cache_write;
+    totals.message_count += row.message_count;
+
+    match find_price(prices, row) {
+        Some(price) => {
+            totals.cost_usd += (tokens.input as f64 * price.input_per_mtok
+                + tokens.output as f64 * price.output_per_mtok
+                + tokens.reasoning as f64 * price.reasoning_per_mtok
+                + tokens.cache_read as f64 * price.cache_read_per_mtok
+                + tokens.cache_write as f64 * price.cache_write_per_mtok)
+                / 1_000_000.0;
+        }
+        None if has_tokens(tokens) => totals.unpriced_message_count += row.message_count,
+        None => {}
+    }
+}
+
+fn group_key(row: &UsageRow, group_by: UsageGroupBy) -> String {
+    let known = |value: &Option<String>| {
+        value
+            .clone()
+            .unwrap_or_else(|| UNKNOWN_USAGE_KEY.to_string())
+    };
+    match group_by {
+        UsageGroupBy::Thread => row.thread_id.clone(),
+        UsageGroupBy::Project => row.project_id.clone(),
+        UsageGroupBy::Model => known(&row.model),
+        UsageGroupBy::Provider => known(&row.provider),
+        UsageGroupBy::Day => utc_date(row.day_start_ms),
+    }
+}
+
+pub fn build_usage_report(
+    rows: &[UsageRow],
+    group_by: UsageGroupBy,
+    prices: &[ModelPrice],
+) -> UsageReport {
+    let mut total = UsageTotals::default();
+    let mut grouped = BTreeMap::<String, UsageTotals>::new();
+    for row in rows {
+        add_row(&mut total, row, prices);
+        add_row(
+            grouped.entry(group_key(row, group_by)).or_default(),
+            row,
+            prices,
+        );
+    }
+
+    let mut groups = grouped
+        .into_iter()
+        .map(|(key, totals)| UsageGroup { key, totals })
+        .collect::<Vec<_>>();
+    if group_by != UsageGroupBy::Day {
+        groups.sort_by(|a, b| b.totals.cost_usd.total_cmp(&a.totals.cost_usd));
+    }
+
+    UsageReport {
+        group_by,
+        groups,
+        total,
+    }
+}
+
+fn budget_applies(budget: &UsageBudget, project_id: Option<&str>, thread_id: Option<&str>) -> bool {
+    match &budget.scope {
+        BudgetScope::Global => true,
+        BudgetScope::Project { project_id: id } => project_id == Some(id.as_str()),
+        BudgetScope::Thread { thread_id: id } => thread_id == Some(id.as_str()),
+    }
+}
+
+pub fn budget_statuses(
+    conn: &Connection,
+    settings: &UsageSettings,
+    project_id: Option<&str>,
+    thread_id: Option<&str>,
+) -> Result<Vec<BudgetStatus>, String> {
+    let now = now_ms();
+    let mut statuses = Vec::new();
+    for budget in &settings.budgets {
+        if !budget_applies(budget, project_id, thread_id) {
+            continue;
+        }
+
+        let since_ms = match budget.period {
+            BudgetPeriod::Day => start_of_utc_day(now),
+            BudgetPeriod::Month => start_of_utc_month(now),
+            BudgetPeriod::Total => 0,
+        };
+        let filter = match &budget.scope {
+            BudgetScope::Global => UsageFilter::default(),
+            BudgetScope::Project { project_id } => UsageFilter {
+                project_id: Some(project_id),
+                ..UsageFilter::default()
+            },
+            BudgetScope::Thread { thread_id } => UsageFilter {
+                thread_id: Some(thread_id),
+                ..UsageFilter::default()
+            },
+        };
+        let rows = query_usage(
+            conn,
+            &UsageFilter {
+                since_ms: Some(since_ms),
+                ..filter
+            },
+        )?;
+        let spent_usd = build_usage_report(&rows, UsageGroupBy::Project, &settings.prices)
+            .total
+            .cost_usd;
+
+        statuses.push(BudgetStatus {
+            budget: budget.clone(),
+            spent_usd,
+            exceeded: spent_usd >= budget.limit_usd,
+        });
+    }
+    Ok(statuses)
+}
+
+pub fn enforce_budgets(
+    root: &Path,
+    conn: &Connection,
+    project_id: &str,
+    thread_id: &str,
+) -> Result<(), String> {
+    let settings = read_usage_settings(root)?;
+    if !settings
+        .budgets
+        .iter()
+        .any(|b| b.action == BudgetAction::Block)
+    {
+        return Ok(());
+    }
+
+    let statuses = budget_statuses(conn, &settings, Some(project_id), Some(thread_id))?;
+    match statuses
+        .iter()
+        .find(|s| s.exceeded && s.budget.action == BudgetAction::Block)
+    {
+        Some(status) => Err(format!(
+            "Budget {} exceeded: ${:.2} spent of ${:.2}",
+            status.budget.id, status.spent_usd, status.budget.limit_usd
+        )),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::core::models::{
+        AgentMode, MessageRecord, MessageRole, ProjectRecord, ProjectSettings, ThreadRecord,
+    };
+    use crate::storage::db::{open_db, put_message, put_project, put_thread};
+    use crate::storage::io::prepare_storage;
+
+    const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+    fn price(model: &str, provider: Option<&str>) -> ModelPrice {
+        ModelPrice {
+            model: model.to_string(),
+            provider: provider.map(str::to_string),
+            input_per_mtok: 2.0,
+            output_per_mtok: 10.0,
+            reasoning_per_mtok: 0.0,
+            cache_read_per_mtok: 0.0,
+            cache_write_per_mtok: 0.0,
+        }
+    }
+
+    fn row(
+        project_id: &str,
+        model: Option<&str>,
+        provider: &str,
+        day: u64,
+        input: u64,
+        message_count: u64,
+    ) -> UsageRow {
+        UsageRow {
+            project_id: project_id.to_string(),
+            thread_id: format!("{project_id}-thread"),
+            model: model.map(str::to_string),
+            provider: Some(provider.to_string()),
+            day_start_ms: day * DAY_MS,
+            tokens: TokenUsage {
+                input,
+                ..TokenUsage::default()
+            },
+            message_count,
+        }
+    }
+
+    #[test]
+    fn report_prices_known_models_and_counts_the_rest() {
+        let rows = [
+            row("p", Some("m"), "a", 1, 1_000_000, 1),
+            row("p", Some("m"), "b", 0, 500_000, 2),
+            row("q", None, "a", 0, 10, 3),
+            row("q", Some("m"), "a", 1, 0, 4),
+        ];
+        let prices = [price("m", Some("a"))];
+
+        let report = build_usage_report(&rows, UsageGroupBy::Model, &prices);
+        assert_eq!(report.total.cost_usd, 2.0);
+        assert_eq!(report.total.message_count, 10);
+        assert_eq!(report.total.tokens.input, 1_500_010);
+        // Provider "b" has no price and the zero-token row costs nothing either way.
+        assert_eq!(report.total.unpriced_message_count, 5);
+        let keys = report
+            .groups
+            .iter()
+            .map(|g| g.key.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, ["m", UNKNOWN_USAGE_KEY]);
+
+        let report = build_usage_report(&rows, UsageGroupBy::Project, &prices);
+        let groups = report
+            .groups
+            .iter()
+            .map(|g| (g.key.as_str(), g.totals.cost_usd, g.totals.message_count))
+            .collect::<Vec<_>>();
+        assert_eq!(groups, [("p", 2.0, 3), ("q", 0.0, 7)]);
+
+        let report = build_usage_report(&rows, UsageGroupBy::Day, &prices);
+        let keys = report
+            .groups
+            .iter()
+            .map(|g| g.key.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, ["1970-01-01", "1970-01-02"]);
+    }
+
+    fn message(conn: &Connection, id: &str, thread_id: &str, sequence: u64, created_at_ms: u64) {
+        put_message(
+            conn,
+            &MessageRecord {
+                id: id.to_string(),
+                thread_id: thread_id.to_string(),
+                role: MessageRole::Agent,
+                content: String::new(),
+                model: Some("m".to_string()),
+                provider: None,
+                mode: AgentMode::Build,
+                tokens: TokenUsage {
+                    input: 1_000_000,
+                    ..TokenUsage::default()
+                },
+                parent_id: None,
+                created_at_ms,
+                updated_at_ms: created_at_ms,
+                sequence,
+                parts: Vec::new(),
+            },
+        )
+        .unwrap();
+    }
+
+    fn budget(id: &str, scope: BudgetScope, period: BudgetPeriod, limit_usd: f64) -> UsageBudget {
+        UsageBudget {
+            id: id.to_string(),
+            scope,
+            period,
+            limit_usd,
+            action: BudgetAction::Block,
+        }
+    }
+
+    #[test]
+    fn budgets_count_spend_in_their_scope_and_period() {
+        let dir = std::env::temp_dir().join(format!("kodit-usage-budgets-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let root = dir.join("storage");
+        prepare_storage(&root).unwrap();
+        let conn = open_db(&root).unwrap();
+        for (project_id, thread_id) in [("p", "t1"), ("q", "t2")] {
+            put_project(
+                &conn,
+                &ProjectRecord {
+                    id: project_id.to_string(),
+                    name: project_id.to_string(),
+                    workspace_path: format!("/{project_id}"),
+                    created_at_ms: 0,
+                    updated_at_ms: 0,
+                    archived: false,
+                    deleted_at_ms: None,
+                    settings: ProjectSettings::default(),
+                },
+            )
+            .unwrap();
+            put_thread(
+                &conn,
+                &ThreadRecord {
+                    id: thread_id.to_string(),
+                    project_id: project_id.to_string(),
+                    title: thread_id.to_string(),
+                    mode: AgentMode::Build,
+                    created_at_ms: 0,
+                    updated_at_ms: 0,
+                    worktree: None,
+                    archived: false,
+                    deleted_at_ms: None,
+                    active_leaf_id: None,
+                    forked_from: None,
+                    todos: Vec::new(),
+                },
+            )
+            .unwrap();
+        }
+        let now = now_ms();
+        message(&conn, "today-1", "t1", 1, now);
+        message(&conn, "today-2", "t2", 1, now);
+        message(&conn, "long-ago", "t1", 2, 1);
+
+        let settings = UsageSettings {
+            prices: vec![price("m", None)],
+            budgets: vec![
+                budget("global", BudgetScope::Global, BudgetPeriod::Day, 4.0),
+                budget(
+                    "project",
+                    BudgetScope::Project {
+                        project_id: "p".to_string(),
+                    },
+                    BudgetPeriod::Total,
+                    10.0,
+                ),
+                budget(
+                    "other-thread",
+                    BudgetScope::Thread {
+                        thread_id: "t2".to_string(),
+                    },
+                    BudgetPeriod::Month,
+                    1.0,
+                ),
+            ],
+        };
+
+        let statuses = budget_statuses(&conn, &settings, Some("p"), Some("t1")).unwrap();
+        let summary = statuses
+            .iter()
+            .map(|s| (s.budget.id.as_str(), s.spent_usd, s.exceeded))
+            .collect::<Vec<_>>();
+        assert_eq!(summary, [("global", 4.0, true), ("project", 4.0, false)]);
+
+        write_usage_settings(&root, &settings).unwrap();
+        assert!(enforce_budgets(&root, &conn, "p", "t1")
+            .unwrap_err()
+            .contains("global"));
+        assert_eq!(read_usage_settings(&root).unwrap().budgets.len(), 3);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
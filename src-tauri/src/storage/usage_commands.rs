@@ -0,0 +1,107 @@
+use tauri::AppHandle;
+
+use crate::core::models::{BudgetStatus, UsageGroupBy, UsageReport, UsageSettings};
+use crate::storage::db::{open_db, require_thread};
+use crate::storage::io::ensure_storage_ready;
+use crate::storage::paths::storage_root;
+use crate::storage::usage::{
+    budget_statuses, build_usage_report, query_usage, read_usage_settings, write_usage_settings,
+    UsageFilter,
+};
+
+#[tauri::command]
+pub fn usage_report(
+    app: AppHandle,
+    group_by: UsageGroupBy,
+    project_id: Option<String>,
+    thread_id: Option<String>,
+    since_ms: Option<u64>,
+    until_ms: Option<u64>,
+) -> Result<UsageReport, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let conn = open_db(&root)?;
+    let settings = read_usage_settings(&root)?;
+
+    let rows = query_usage(
+        &conn,
+        &UsageFilter {
+            project_id: project_id.as_deref(),
+            thread_id: thread_id.as_deref(),
+            since_ms,
+            until_ms,
+        },
+    )?;
+    Ok(build_usage_report(&rows, group_by, &settings.prices))
+}
+
+#[tauri::command]
+pub fn get_usage_settings(app: AppHandle) -> Result<UsageSettings, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    read_usage_settings(&root)
+}
+
+fn is_valid_amount(amount: f64) -> bool {
+    amount.is_finite() && amount >= 0.0
+}
+
+#[tauri::command]
+pub fn set_usage_settings(
+    app: AppHandle,
+    settings: UsageSettings,
+) -> Result<UsageSettings, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    if let Some(budget) = settings
+        .budgets
+        .iter()
+        .find(|b| !is_valid_amount(b.limit_usd))
+    {
+        return Err(format!(
+            "Budget {} needs a limit of zero or more",
+            budget.id
+        ));
+    }
+    if let Some(price) = settings.prices.iter().find(|p| {
+        [
+            p.input_per_mtok,
+            p.output_per_mtok,
+            p.reasoning_per_mtok,
+            p.cache_read_per_mtok,
+            p.cache_write_per_mtok,
+        ]
+        .into_iter()
+        .any(|amount| !is_valid_amount(amount))
+    }) {
+        return Err(format!(
+            "Prices for {} must be zero or more",
+            price.model
+        ));
+    }
+    write_usage_settings(&root, &settings)?;
+    Ok(settings)
+}
+
+#[tauri::command]
+pub fn check_budgets(
+    app: AppHandle,
+    project_id: Option<String>,
+    thread_id: Option<String>,
+) -> Result<Vec<BudgetStatus>, String> {
+    let root = storage_root(&app)?;
+    let _lock = ensure_storage_ready(&root)?;
+    let conn = open_db(&root)?;
+    let settings = read_usage_settings(&root)?;
+
+    let project_id = match &thread_id {
+        Some(thread_id) => Some(require_thread(&conn, thread_id)?.project_id),
+        None => project_id,
+    };
+    budget_statuses(
+        &conn,
+        &settings,
+        project_id.as_deref(),
+        thread_id.as_deref(),
+    )
+}
@@ -10,7 +10,11 @@ import { SettingsPage } from "@/pages/SettingsPage";
 import { loadStoredSettings } from "@/lib/settings-storage";
 import { providerPresets } from "@/lib/ai/providers";
 import { fetchProviderModels, validateProviderApiKey } from "@/lib/ai";
-import { loadAuthApiKeys } from "@/lib/auth/auth-storage";
+import {
+    loadAuthApiKeys,
+    type AuthApiKeysResult,
+} from "@/lib/auth/auth-storage";
+import { UnlockAuthDialog } from "@/components/auth/UnlockAuthDialog";
 import {
     initializeStorage,
     listProjects,
@@ -23,6 +27,27 @@ import { buildThreadDiffStats } from "@/lib/diff-utils";
 function App() {
     const [state, dispatch] = useReducer(appReducer, initialState);
 
+    function applyAuthResult(result: AuthApiKeysResult) {
+        dispatch({
+            type: "SET_AUTH_STATE",
+            locked: result.status === "locked",
+            error: result.status === "error" ? result.error : null,
+        });
+        if (result.status !== "ready") {
+            return;
+        }
+
+        for (const [providerId, apiKey] of Object.entries(result.apiKeys)) {
+            if (apiKey.trim()) {
+                dispatch({
+                    type: "SET_PROVIDER_API_KEY",
+                    providerId,
+                    apiKey: apiKey.trim(),
+                });
+            }
+        }
+    }
+
     useEffect(() => {
         let cancelled = false;
 
@@ -149,18 +174,13 @@ function App() {
 
         async function bootstrapSettings() {
             const stored = loadStoredSettings();
-            const authKeys = await loadAuthApiKeys();
+            const authResult = await loadAuthApiKeys();
 
             if (cancelled) {
                 return;
             }
 
-            const mergedKeys = {
-                ...stored.apiKeys,
-                ...authKeys,
-            };
-
-            for (const [providerId, apiKey] of Object.entries(mergedKeys)) {
+            for (const [providerId, apiKey] of Object.entries(stored.apiKeys)) {
                 if (apiKey.trim()) {
                     dispatch({
                         type: "SET_PROVIDER_API_KEY",
@@ -169,6 +189,8 @@ function App() {
                     });
                 }
             }
+            // Keys from auth storage override the local settings copy.
+            applyAuthResult(authResult);
 
             if (stored.window) {
                 dispatch({
@@ -247,6 +269,18 @@ function App() {
             enableSystem={false}
         >
             <AppContext.Provider value={{ state, dispatch }}>
+                <UnlockAuthDialog
+                    locked={state.authLocked}
+                    error={state.authError}
+                    onResult={applyAuthResult}
+                    onDismiss={() =>
+                        dispatch({
+                            type: "SET_AUTH_STATE",
+                            locked: false,
+                            error: null,
+                        })
+                    }
+                />
                 <BrowserRouter>
                     <div className="flex h-screen w-screen flex-col overflow-hidden bg-sidebar">
                         <div className="flex flex-1 overflow-hidden">
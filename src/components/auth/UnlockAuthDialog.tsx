@@ -0,0 +1,130 @@
+import { useState } from "react";
+import {
+    Dialog,
+    DialogContent,
+    DialogDescription,
+    DialogFooter,
+    DialogHeader,
+    DialogTitle,
+} from "@/components/ui/dialog";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { loadAuthApiKeys, type AuthApiKeysResult } from "@/lib/auth/auth-storage";
+import { unlockAuth } from "@/lib/tauri-storage";
+
+interface UnlockAuthDialogProps {
+    locked: boolean;
+    error: string | null;
+    onResult: (result: AuthApiKeysResult) => void;
+    onDismiss: () => void;
+}
+
+export function UnlockAuthDialog({
+    locked,
+    error,
+    onResult,
+    onDismiss,
+}: UnlockAuthDialogProps) {
+    const [passphrase, setPassphrase] = useState("");
+    const [unlockError, setUnlockError] = useState<string | null>(null);
+    const [busy, setBusy] = useState(false);
+
+    async function handleUnlock(event: React.FormEvent) {
+        event.preventDefault();
+        if (!passphrase) return;
+
+        setBusy(true);
+        setUnlockError(null);
+        try {
+            await unlockAuth(passphrase);
+            setPassphrase("");
+            onResult(await loadAuthApiKeys());
+        } catch (unlockFailure) {
+            setUnlockError(
+                unlockFailure instanceof Error
+                    ? unlockFailure.message
+                    : String(unlockFailure),
+            );
+        } finally {
+            setBusy(false);
+        }
+    }
+
+    async function handleRetry() {
+        setBusy(true);
+        try {
+            onResult(await loadAuthApiKeys());
+        } finally {
+            setBusy(false);
+        }
+    }
+
+    return (
+        <Dialog
+            open={locked || error !== null}
+            onOpenChange={(open) => {
+                if (!open) onDismiss();
+            }}
+        >
+            <DialogContent>
+                {locked ? (
+                    <form className="grid gap-6" onSubmit={handleUnlock}>
+                        <DialogHeader>
+                            <DialogTitle>Unlock API keys</DialogTitle>
+                            <DialogDescription>
+                                Your stored API keys are protected by a
+                                passphrase. Enter it to use them.
+                            </DialogDescription>
+                        </DialogHeader>
+                        <div className="space-y-2">
+                            <Label htmlFor="auth-passphrase">Passphrase</Label>
+                            <Input
+                                id="auth-passphrase"
+                                type="password"
+                                autoComplete="current-password"
+                                autoFocus
+                                value={passphrase}
+                                onChange={(event) =>
+                                    setPassphrase(event.target.value)
+                                }
+                            />
+                            {unlockError && (
+                                <p className="text-xs text-destructive">
+                                    {unlockError}
+                                </p>
+                            )}
+                        </div>
+                        <DialogFooter>
+                            <Button
+                                type="button"
+                                variant="outline"
+                                onClick={onDismiss}
+                            >
+                                Not now
+                            </Button>
+                            <Button type="submit" disabled={busy || !passphrase}>
+                                {busy ? "Unlocking..." : "Unlock"}
+                            </Button>
+                        </DialogFooter>
+                    </form>
+                ) : (
+                    <>
+                        <DialogHeader>
+                            <DialogTitle>API keys could not be loaded</DialogTitle>
+                            <DialogDescription>{error}</DialogDescription>
+                        </DialogHeader>
+                        <DialogFooter>
+                            <Button variant="outline" onClick={onDismiss}>
+                                Dismiss
+                            </Button>
+                            <Button onClick={handleRetry} disabled={busy}>
+                                Try again
+                            </Button>
+                        </DialogFooter>
+                    </>
+                )}
+            </DialogContent>
+        </Dialog>
+    );
+}
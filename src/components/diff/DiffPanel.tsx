@@ -57,7 +57,7 @@ export function DiffPanel() {
             for (const snapshot of snapshots) {
                 if (snapshot.oldContent === null) {
                     await agentDeleteFile({
-                        workspacePath: activeProject.workspacePath,
+                        threadId: activeThread.id,
                         path: snapshot.filePath,
                         allowMissing: true,
                     });
@@ -65,7 +65,7 @@ export function DiffPanel() {
                 }
 
                 await agentWriteFile({
-                    workspacePath: activeProject.workspacePath,
+                    threadId: activeThread.id,
                     path: snapshot.filePath,
                     content: snapshot.oldContent,
                     createDirs: true,
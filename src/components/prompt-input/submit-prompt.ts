@@ -179,6 +179,7 @@ export async function submitPrompt({
             modelId: state.selectedModel.id,
             messages: [...previousMessages, userMessage],
             workspacePath: activeWorkspacePath,
+            projectId: activeProjectId ?? undefined,
             threadId,
             initialTodos: currentThread?.todos ?? [],
             onChunk: (chunk) =>
@@ -4,6 +4,7 @@ import { getProviderPreset } from "@/lib/ai/providers";
 import { fetchProviderModels, validateProviderApiKey } from "@/lib/ai";
 import { saveStoredSettings } from "@/lib/settings-storage";
 import { saveAuthApiKey } from "@/lib/auth/auth-storage";
+import { getAuthStatus } from "@/lib/tauri-storage";
 import type { AppAction } from "@/store/app-store";
 import type { AppState, ModelConfig } from "@/store/types";
 import type { SettingsPageId } from "@/components/settings/Nav";
@@ -94,7 +95,19 @@ export function useSettingsPage({ state, dispatch }: UseSettingsPageInput) {
         const nextApiKeys = { ...state.settings.apiKeys, [providerId]: apiKey };
 
         persistSettings({ apiKeys: nextApiKeys });
-        await saveAuthApiKey(providerId, apiKey);
+        try {
+            await saveAuthApiKey(providerId, apiKey);
+        } catch (error) {
+            setProviderErrorById((prev) => ({
+                ...prev,
+                [providerId]: error instanceof Error ? error.message : String(error),
+            }));
+            const auth = await getAuthStatus().catch(() => null);
+            if (auth?.locked) {
+                dispatch({ type: "SET_AUTH_STATE", locked: true, error: null });
+            }
+            return;
+        }
 
         setApiKeyInputByProvider((prev) => ({ ...prev, [providerId]: apiKey }));
         setProviderErrorById((prev) => ({ ...prev, [providerId]: null }));
@@ -1,5 +1,6 @@
 import { ToolLoopAgent, stepCountIs } from "ai";
 import { koditSystemPrompt } from "@/lib/ai/prompts/kodit-system";
+import { buildSystemPrompt } from "@/lib/ai/prompts/project-instructions";
 import { getProviderPreset } from "@/lib/ai/providers";
 import {
     createWorkspaceTools,
@@ -9,6 +10,7 @@ import {
 } from "@/lib/ai/tools/workspace-tools";
 import type { Message, ModelConfig, ToolCall } from "@/store/types";
 import type { TodoItem } from "@/lib/ai/tools/todo-store";
+import { loadProjectInstructions } from "@/lib/tauri-storage";
 
 export type { QuestionInput, QuestionAnswer } from "@/lib/ai/question-bridge";
 export type { TodoItem } from "@/lib/ai/tools/todo-store";
@@ -19,6 +21,7 @@ interface StreamTextParams {
     modelId: string;
     messages: Message[];
     workspacePath: string;
+    projectId?: string;
     threadId: string;
     initialTodos?: TodoItem[];
     onChunk: (chunk: string) => void;
@@ -55,6 +58,7 @@ export async function streamProviderText({
     modelId,
     messages,
     workspacePath,
+    projectId,
     threadId,
     initialTodos = [],
     onChunk,
@@ -96,10 +100,19 @@ export async function streamProviderText({
         threadId,
     });
 
+    const projectInstructions = projectId
+        ? await loadProjectInstructions({ projectId, threadId }).catch(
+              () => null,
+          )
+        : null;
+
     const agent = new ToolLoopAgent({
         model: providerPreset.createModel(apiKey, modelId),
         stopWhen: stepCountIs(20),
-        instructions: koditSystemPrompt.text,
+        instructions: buildSystemPrompt(
+            koditSystemPrompt.text,
+            projectInstructions,
+        ),
         tools,
     });
 
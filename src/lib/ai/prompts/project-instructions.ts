@@ -0,0 +1,30 @@
+import type { ProjectInstructions } from "@/lib/tauri-storage";
+
+/**
+ * Appends the project's custom instructions and instruction files to the
+ * base system prompt, parents before nested files, each under its path.
+ */
+export function buildSystemPrompt(
+    base: string,
+    instructions: ProjectInstructions | null,
+): string {
+    if (!instructions) {
+        return base;
+    }
+
+    const sections: string[] = [];
+    if (instructions.settingsInstructions) {
+        sections.push(
+            `## Project settings\n\n${instructions.settingsInstructions}`,
+        );
+    }
+    for (const file of instructions.files) {
+        const scope = file.scope ? ` (applies to ${file.scope}/)` : "";
+        sections.push(`## ${file.path}${scope}\n\n${file.content.trim()}`);
+    }
+    if (sections.length === 0) {
+        return base;
+    }
+
+    return `${base}\n\n# Project instructions\n\n${sections.join("\n\n")}`;
+}
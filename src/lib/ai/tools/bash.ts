@@ -3,7 +3,14 @@ import { z } from "zod";
 import { agentRunCommand } from "@/lib/tauri-storage";
 import DESCRIPTION from "./bash.txt";
 
-export function createShellTool(workspacePath: string) {
+interface CreateShellToolOptions {
+    threadId: string;
+}
+
+export function createShellTool(
+    workspacePath: string,
+    options: CreateShellToolOptions,
+) {
     return tool({
         description: DESCRIPTION,
         inputSchema: z.object({
@@ -21,7 +28,7 @@ export function createShellTool(workspacePath: string) {
         }),
         execute: async ({ command, workdir, timeout }) => {
             return agentRunCommand({
-                workspacePath,
+                threadId: options.threadId,
                 command,
                 workdir: workdir ? `${workspacePath}/${workdir}` : undefined,
                 timeoutMs: timeout,
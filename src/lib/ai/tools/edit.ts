@@ -8,21 +8,15 @@ function normalizeNewlines(text: string): string {
 }
 
 interface CreateEditToolOptions {
-    threadId?: string;
+    threadId: string;
 }
 
-export function createEditTool(
-    workspacePath: string,
-    options: CreateEditToolOptions = {},
-) {
+export function createEditTool(options: CreateEditToolOptions) {
     async function recordDiff(
         path: string,
         oldContent: string,
         newContent: string,
     ) {
-        if (!options.threadId) {
-            return;
-        }
         try {
             await saveDiff({
                 threadId: options.threadId,
@@ -66,10 +60,13 @@ export function createEditTool(
             }
 
             // Read the current file content
-            const initialRead = await agentReadFile({ workspacePath, path });
+            const initialRead = await agentReadFile({
+                threadId: options.threadId,
+                path,
+            });
             const readResult = initialRead.truncated
                 ? await agentReadFile({
-                      workspacePath,
+                      threadId: options.threadId,
                       path,
                       offset: 0,
                       limit: initialRead.totalLines + 1,
@@ -92,7 +89,7 @@ export function createEditTool(
                     .split(normalizedOld)
                     .join(normalizedNew);
                 await agentWriteFile({
-                    workspacePath,
+                    threadId: options.threadId,
                     path,
                     content: updated,
                     createDirs: false,
@@ -121,7 +118,7 @@ export function createEditTool(
                 content.slice(firstIndex + normalizedOld.length);
 
             await agentWriteFile({
-                workspacePath,
+                threadId: options.threadId,
                 path,
                 content: updated,
                 createDirs: false,
@@ -3,7 +3,11 @@ import { z } from "zod";
 import { agentReadFile } from "@/lib/tauri-storage";
 import DESCRIPTION from "./read.txt";
 
-export function createReadFileTool(workspacePath: string) {
+interface CreateReadFileToolOptions {
+    threadId: string;
+}
+
+export function createReadFileTool(options: CreateReadFileToolOptions) {
     return tool({
         description: DESCRIPTION,
         inputSchema: z.object({
@@ -20,7 +24,7 @@ export function createReadFileTool(workspacePath: string) {
         }),
         execute: async ({ filePath, offset, limit }) => {
             return agentReadFile({
-                workspacePath,
+                threadId: options.threadId,
                 path: filePath,
                 offset,
                 limit,
@@ -2,9 +2,17 @@ import { tool } from "ai";
 import { z } from "zod";
 import { todoSchema } from "./todo-store";
 import type { TodoStore } from "./todo-store";
+import { writeTodos } from "@/lib/tauri-storage";
 import DESCRIPTION from "./todo-write.txt";
 
-export function createTodoWriteTool(store: TodoStore) {
+interface CreateTodoWriteToolOptions {
+    threadId?: string;
+}
+
+export function createTodoWriteTool(
+    store: TodoStore,
+    options: CreateTodoWriteToolOptions = {},
+) {
     return tool({
         description: DESCRIPTION,
         inputSchema: z.object({
@@ -12,6 +20,13 @@ export function createTodoWriteTool(store: TodoStore) {
         }),
         execute: async ({ todos }) => {
             store.set(todos);
+            if (options.threadId) {
+                try {
+                    await writeTodos(options.threadId, todos);
+                } catch {
+                    // The in-memory list stays authoritative for this session.
+                }
+            }
             return {
                 todos: store.get(),
                 summary: store.summary(),
@@ -14,21 +14,21 @@ export interface WorkspaceToolsContext {
 
 interface CreateWorkspaceToolsOptions {
     initialTodos?: TodoItem[];
-    threadId?: string;
+    threadId: string;
 }
 
 export function createWorkspaceTools(
     workspacePath: string,
-    { initialTodos = [], threadId }: CreateWorkspaceToolsOptions = {},
+    { initialTodos = [], threadId }: CreateWorkspaceToolsOptions,
 ) {
     const todoStore = createTodoStore();
     todoStore.set(initialTodos);
 
     const tools = {
-        read_file: createReadFileTool(workspacePath),
-        shell: createShellTool(workspacePath),
-        edit: createEditTool(workspacePath, { threadId }),
-        todo_write: createTodoWriteTool(todoStore),
+        read_file: createReadFileTool({ threadId }),
+        shell: createShellTool(workspacePath, { threadId }),
+        edit: createEditTool({ threadId }),
+        todo_write: createTodoWriteTool(todoStore, { threadId }),
         todo_read: createTodoReadTool(todoStore),
         question: createQuestionTool(),
     };
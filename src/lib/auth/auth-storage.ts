@@ -1,11 +1,32 @@
-import { readAuthConfig, writeAuthConfig } from "@/lib/tauri-storage";
+import {
+    getAuthStatus,
+    readAuthConfig,
+    writeAuthConfig,
+} from "@/lib/tauri-storage";
 
-export async function loadAuthApiKeys(): Promise<Record<string, string>> {
+export type AuthApiKeysResult =
+    | { status: "ready"; apiKeys: Record<string, string> }
+    | { status: "locked" }
+    | { status: "error"; error: string };
+
+function errorMessage(error: unknown): string {
+    return error instanceof Error ? error.message : String(error);
+}
+
+/**
+ * Loads stored API keys. A locked or unreadable store is reported as such
+ * rather than as having no keys, so the caller can ask to unlock it.
+ */
+export async function loadAuthApiKeys(): Promise<AuthApiKeysResult> {
     try {
+        const status = await getAuthStatus();
+        if (status.locked) {
+            return { status: "locked" };
+        }
         const config = await readAuthConfig();
-        return config.apiKeys;
-    } catch {
-        return {};
+        return { status: "ready", apiKeys: config.apiKeys };
+    } catch (error) {
+        return { status: "error", error: errorMessage(error) };
     }
 }
 
@@ -13,9 +34,11 @@ export async function saveAuthApiKey(
     providerId: string,
     apiKey: string,
 ): Promise<Record<string, string>> {
-    const current = await loadAuthApiKeys();
+    // Read strictly: falling back to no keys here would drop every other
+    // provider's key when the store is locked or damaged.
+    const current = await readAuthConfig();
     const nextApiKeys = {
-        ...current,
+        ...current.apiKeys,
         [providerId]: apiKey,
     };
 
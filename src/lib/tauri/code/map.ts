@@ -17,6 +17,7 @@ export function mapProject(project: BackendProject): Project {
         workspacePath: project.workspace_path,
         createdAt: project.created_at_ms,
         updatedAt: project.updated_at_ms,
+        workspaceMissing: project.workspace_missing ?? false,
     };
 }
 
@@ -28,7 +29,7 @@ export function mapThread(thread: BackendThread): Thread {
         createdAt: thread.created_at_ms,
         updatedAt: thread.updated_at_ms,
         messages: [],
-        todos: [],
+        todos: thread.todos ?? [],
         fileChanges: [],
         totalAdditions: 0,
         totalDeletions: 0,
@@ -1,27 +1,50 @@
 export type {
     AuthConfig,
+    AuthStatus,
+    BackendProjectSettings,
+    BackendProjectSettingsView,
     DiffRecord,
     FileChangeType,
     FileSnapshotChange,
 } from "./types/model";
 export { init as initializeStorage, folder as pickFolder } from "./ops/base";
 export {
-    createDefaultProjectIfNeeded,
     createProject,
+    getProjectSettings,
     listProjects,
+    relocateProject,
+    setProjectSettings,
 } from "./ops/project";
-export { addMessage, createThread, listMessages, listThreads } from "./ops/thread";
+export {
+    addMessage,
+    createThread,
+    listMessages,
+    listThreads,
+    readTodos,
+    writeTodos,
+} from "./ops/thread";
 export { clearDiffs, listDiffs, saveDiff } from "./ops/diff";
 export {
     agentDeleteFile,
     agentReadFile,
     agentRunCommand,
     agentWriteFile,
+    loadProjectInstructions,
 } from "./ops/agent";
 export type {
     AgentDeleteFileResult,
     AgentReadFileResult,
     AgentRunCommandResult,
     AgentWriteFileResult,
+    InstructionFile,
+    ProjectInstructions,
 } from "./ops/agent";
-export { readAuthConfig, writeAuthConfig } from "./ops/auth";
+export {
+    getAuthStatus,
+    lockAuth,
+    readAuthConfig,
+    setAuthAutoLock,
+    setAuthPassphrase,
+    unlockAuth,
+    writeAuthConfig,
+} from "./ops/auth";
@@ -28,8 +28,23 @@ export interface AgentRunCommandResult {
     timedOut: boolean;
 }
 
-export async function agentReadFile(params: {
+export interface InstructionFile {
+    path: string;
+    scope: string;
+    kind: "agents" | "rule";
+    content: string;
+    includes: string[];
+}
+
+export interface ProjectInstructions {
     workspacePath: string;
+    settingsInstructions: string | null;
+    files: InstructionFile[];
+    warnings: string[];
+}
+
+export async function agentReadFile(params: {
+    threadId: string;
     path: string;
     offset?: number;
     limit?: number;
@@ -54,7 +69,7 @@ export async function agentReadFile(params: {
 }
 
 export async function agentWriteFile(params: {
-    workspacePath: string;
+    threadId: string;
     path: string;
     content: string;
     createDirs?: boolean;
@@ -74,7 +89,7 @@ export async function agentWriteFile(params: {
 }
 
 export async function agentDeleteFile(params: {
-    workspacePath: string;
+    threadId: string;
     path: string;
     allowMissing?: boolean;
 }): Promise<AgentDeleteFileResult> {
@@ -93,7 +108,7 @@ export async function agentDeleteFile(params: {
 }
 
 export async function agentRunCommand(params: {
-    workspacePath: string;
+    threadId: string;
     command: string;
     workdir?: string;
     timeoutMs?: number;
@@ -116,3 +131,22 @@ export async function agentRunCommand(params: {
         timedOut: result.timed_out,
     };
 }
+
+export async function loadProjectInstructions(params: {
+    projectId: string;
+    threadId?: string;
+}): Promise<ProjectInstructions> {
+    const result = await invoke<{
+        workspace_path: string;
+        settings_instructions: string | null;
+        files: InstructionFile[];
+        warnings: string[];
+    }>("load_project_instructions", params);
+
+    return {
+        workspacePath: result.workspace_path,
+        settingsInstructions: result.settings_instructions,
+        files: result.files,
+        warnings: result.warnings,
+    };
+}
@@ -1,5 +1,19 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { AuthConfig, BackendAuthConfig } from "../types/model";
+import type {
+    AuthConfig,
+    AuthStatus,
+    BackendAuthConfig,
+    BackendAuthStatus,
+} from "../types/model";
+
+function mapAuthStatus(status: BackendAuthStatus): AuthStatus {
+    return {
+        backend: status.backend,
+        passphraseSet: status.passphrase_set,
+        locked: status.locked,
+        autoLockMs: status.auto_lock_ms,
+    };
+}
 
 export async function readAuthConfig(): Promise<AuthConfig> {
     const config = await invoke<BackendAuthConfig>("read_auth_config");
@@ -13,3 +27,34 @@ export async function writeAuthConfig(config: AuthConfig): Promise<AuthConfig> {
 
     return { apiKeys: next.api_keys ?? {} };
 }
+
+export async function getAuthStatus(): Promise<AuthStatus> {
+    return mapAuthStatus(await invoke<BackendAuthStatus>("auth_status"));
+}
+
+export async function unlockAuth(passphrase: string): Promise<AuthStatus> {
+    return mapAuthStatus(await invoke<BackendAuthStatus>("unlock_auth", { passphrase }));
+}
+
+export async function lockAuth(): Promise<AuthStatus> {
+    return mapAuthStatus(await invoke<BackendAuthStatus>("lock_auth"));
+}
+
+/** Pass `null` as `passphrase` to remove it; changing or removing one needs the current passphrase. */
+export async function setAuthPassphrase(
+    passphrase: string | null,
+    currentPassphrase?: string,
+): Promise<AuthStatus> {
+    return mapAuthStatus(
+        await invoke<BackendAuthStatus>("set_auth_passphrase", {
+            passphrase,
+            currentPassphrase: currentPassphrase ?? null,
+        }),
+    );
+}
+
+export async function setAuthAutoLock(autoLockMs: number): Promise<AuthStatus> {
+    return mapAuthStatus(
+        await invoke<BackendAuthStatus>("set_auth_auto_lock", { autoLockMs }),
+    );
+}
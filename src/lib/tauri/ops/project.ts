@@ -1,6 +1,11 @@
 import { invoke } from "@tauri-apps/api/core";
 import { mapProject } from "../code/map";
-import type { BackendProject, Project } from "../types/model";
+import type {
+    BackendProject,
+    BackendProjectSettings,
+    BackendProjectSettingsView,
+    Project,
+} from "../types/model";
 
 export async function listProjects(): Promise<Project[]> {
     const projects = await invoke<BackendProject[]>("list_projects");
@@ -20,9 +25,31 @@ export async function createProject(params: {
     return mapProject(created);
 }
 
-export async function createDefaultProjectIfNeeded(): Promise<Project> {
-    return createProject({
-        name: "Default Project",
-        workspacePath: "default-workspace",
+export async function relocateProject(
+    projectId: string,
+    workspacePath: string,
+): Promise<Project> {
+    const project = await invoke<BackendProject>("relocate_project", {
+        projectId,
+        workspacePath,
+    });
+    return mapProject(project);
+}
+
+export async function getProjectSettings(
+    projectId: string,
+): Promise<BackendProjectSettingsView> {
+    return invoke<BackendProjectSettingsView>("get_project_settings", {
+        projectId,
+    });
+}
+
+export async function setProjectSettings(
+    projectId: string,
+    settings: BackendProjectSettings,
+): Promise<BackendProjectSettingsView> {
+    return invoke<BackendProjectSettingsView>("set_project_settings", {
+        projectId,
+        settings,
     });
 }
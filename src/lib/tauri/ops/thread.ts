@@ -4,9 +4,11 @@ import { encode } from "../code/meta";
 import type {
     AddMessageInput,
     BackendMessage,
+    BackendMode,
     BackendThread,
     Message,
     Thread,
+    TodoItem,
 } from "../types/model";
 
 export async function listThreads(projectId: string): Promise<Thread[]> {
@@ -17,7 +19,7 @@ export async function listThreads(projectId: string): Promise<Thread[]> {
 export async function createThread(params: {
     projectId: string;
     title: string;
-    mode?: "build";
+    mode?: BackendMode;
 }): Promise<Thread> {
     const thread = await invoke<BackendThread>("upsert_thread", {
         input: {
@@ -64,3 +66,14 @@ export async function addMessage(params: AddMessageInput): Promise<Message> {
 
     return mapMessage(message);
 }
+
+export async function readTodos(threadId: string): Promise<TodoItem[]> {
+    return invoke<TodoItem[]>("read_todos", { threadId });
+}
+
+export async function writeTodos(
+    threadId: string,
+    todos: TodoItem[],
+): Promise<TodoItem[]> {
+    return invoke<TodoItem[]>("write_todos", { threadId, todos });
+}
@@ -8,7 +8,7 @@ import type {
 } from "@/store/types";
 
 export type BackendMessageRole = "user" | "agent" | "system";
-export type BackendMode = "build";
+export type BackendMode = "build" | "plan" | "ask" | "review";
 
 export interface BackendTokenUsage {
     input: number;
@@ -24,6 +24,24 @@ export interface BackendProject {
     workspace_path: string;
     created_at_ms: number;
     updated_at_ms: number;
+    workspace_missing?: boolean;
+}
+
+export interface BackendProjectSettings {
+    model?: string | null;
+    provider?: string | null;
+    mode?: BackendMode | null;
+    command_timeout_ms?: number | null;
+    protected_paths?: string[];
+    env?: Record<string, string>;
+    instructions?: string | null;
+}
+
+export interface BackendProjectSettingsView {
+    stored: BackendProjectSettings;
+    workspace: BackendProjectSettings | null;
+    workspace_error: string | null;
+    effective: BackendProjectSettings;
 }
 
 export interface BackendThread {
@@ -33,8 +51,27 @@ export interface BackendThread {
     mode: BackendMode;
     created_at_ms: number;
     updated_at_ms: number;
+    todos?: TodoItem[];
 }
 
+export type BackendMessagePart =
+    | { type: "text"; text: string }
+    | { type: "reasoning"; text: string }
+    | { type: "tool_call"; id: string; name: string; args: unknown }
+    | {
+          type: "tool_result";
+          tool_call_id: string;
+          status: ToolCall["status"];
+          output?: string | null;
+      }
+    | {
+          type: "attachment";
+          name: string;
+          mime_type: string;
+          path?: string | null;
+          data?: string | null;
+      };
+
 export interface BackendMessage {
     id: string;
     thread_id: string;
@@ -48,6 +85,7 @@ export interface BackendMessage {
     created_at_ms: number;
     updated_at_ms: number;
     sequence: number;
+    parts?: BackendMessagePart[];
 }
 
 export type BackendFileChangeType = "created" | "modified" | "deleted";
@@ -84,6 +122,22 @@ export interface AuthConfig {
     apiKeys: Record<string, string>;
 }
 
+export type SecretBackend = "keyring" | "file";
+
+export interface BackendAuthStatus {
+    backend: SecretBackend;
+    passphrase_set: boolean;
+    locked: boolean;
+    auto_lock_ms: number;
+}
+
+export interface AuthStatus {
+    backend: SecretBackend;
+    passphraseSet: boolean;
+    locked: boolean;
+    autoLockMs: number;
+}
+
 export type FileChangeType = BackendFileChangeType;
 
 export interface FileSnapshotChange {
@@ -49,6 +49,7 @@ export type AppAction =
     | { type: "SET_ACTIVE_PROJECT"; projectId: string }
     | { type: "SET_AVAILABLE_MODELS"; models: ModelConfig[] }
     | { type: "SET_PROVIDER_API_KEY"; providerId: string; apiKey: string }
+    | { type: "SET_AUTH_STATE"; locked: boolean; error: string | null }
     | {
           type: "SET_MODEL_PROFILES";
           profiles: ModelConfig[];
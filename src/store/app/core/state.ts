@@ -21,6 +21,8 @@ export const initialState: AppState = {
     modelsError: null,
     storageLoading: true,
     storageError: null,
+    authLocked: false,
+    authError: null,
     sidebarCollapsed: false,
     diffPanelOpen: true,
 };
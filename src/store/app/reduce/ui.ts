@@ -32,6 +32,8 @@ export function appReducer2(state: AppState, action: AppAction): AppState {
                     },
                 },
             };
+        case "SET_AUTH_STATE":
+            return { ...state, authLocked: action.locked, authError: action.error };
         case "SET_MODEL_PROFILES": {
             const deduped = action.profiles.filter(
                 (model, index, models) =>
@@ -38,7 +38,7 @@ export interface Message {
     timestamp: number;
     model?: string;
     provider?: string;
-    mode?: "build";
+    mode?: "build" | "plan" | "ask" | "review";
     parentId?: string | null;
     tokens?: {
         input: number;
@@ -104,6 +104,7 @@ export interface Project {
     workspacePath: string;
     createdAt: number;
     updatedAt: number;
+    workspaceMissing?: boolean;
 }
 
 export interface ModelConfig {
@@ -136,6 +137,8 @@ export interface AppState {
     modelsError: string | null;
     storageLoading: boolean;
     storageError: string | null;
+    authLocked: boolean;
+    authError: string | null;
     sidebarCollapsed: boolean;
     diffPanelOpen: boolean;
 }